@@ -0,0 +1,247 @@
+//! Bounding-volume hierarchy used to accelerate ray intersection against a
+//! set of primitives (scene objects or mesh triangles). Nodes are built with
+//! the surface-area heuristic (SAH) and stored in a flat `Vec` in depth-first
+//! order for cache-friendly traversal: an interior node's left child is the
+//! very next entry, and its right child is reached via a stored skip index.
+
+use crate::algebra::Vec3;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn empty() -> Self {
+        Self {
+            min: Vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points(a: Vec3, b: Vec3) -> Self {
+        Self {
+            min: Vec3(a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+            max: Vec3(a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+        }
+    }
+
+    pub fn union(self, o: Aabb) -> Self {
+        Self {
+            min: Vec3(self.min.0.min(o.min.0), self.min.1.min(o.min.1), self.min.2.min(o.min.2)),
+            max: Vec3(self.max.0.max(o.max.0), self.max.1.max(o.max.1), self.max.2.max(o.max.2)),
+        }
+    }
+
+    pub fn grow(self, p: Vec3) -> Self {
+        Self {
+            min: Vec3(self.min.0.min(p.0), self.min.1.min(p.1), self.min.2.min(p.2)),
+            max: Vec3(self.max.0.max(p.0), self.max.1.max(p.1), self.max.2.max(p.2)),
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max).scale(0.5)
+    }
+
+    /// Longest-extent axis of this box: 0 = x, 1 = y, 2 = z.
+    pub fn longest_axis(&self) -> usize {
+        let ext = self.max - self.min;
+        if ext.0 > ext.1 && ext.0 > ext.2 {
+            0
+        } else if ext.1 > ext.2 {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Surface area, used as the SAH split-cost weight; zero for an empty box.
+    pub fn surface_area(&self) -> f32 {
+        let d = self.max - self.min;
+        if d.0 < 0.0 || d.1 < 0.0 || d.2 < 0.0 { return 0.0; }
+        2.0 * (d.0 * d.1 + d.1 * d.2 + d.2 * d.0)
+    }
+
+    fn axis(v: Vec3, axis: usize) -> f32 {
+        match axis {
+            0 => v.0,
+            1 => v.1,
+            _ => v.2,
+        }
+    }
+
+    /// Ray-slab test. Returns whether the ray's `[t_min, t_max]` range
+    /// overlaps the interval over which it is inside the box.
+    pub fn hit(&self, ro: Vec3, inv_rd: Vec3, t_min: f32, t_max: f32) -> bool {
+        let mut t_near = t_min;
+        let mut t_far = t_max;
+        for axis in 0..3 {
+            let o = Self::axis(ro, axis);
+            let d = Self::axis(inv_rd, axis);
+            let mut t0 = (Self::axis(self.min, axis) - o) * d;
+            let mut t1 = (Self::axis(self.max, axis) - o) * d;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_near = t_near.max(t0);
+            t_far = t_far.min(t1);
+            if t_near > t_far {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// One node of the flattened BVH. A leaf has `count > 0` and `start` indexes
+/// the `[start, start+count)` range of the caller-owned, BVH-order-permuted
+/// primitive array. An interior node has `count == 0`; its left child is the
+/// next entry in `nodes`, and `right_child` is the index of its right child.
+struct FlatNode {
+    aabb: Aabb,
+    start: usize,
+    count: u32,
+    right_child: usize,
+    split_axis: usize,
+}
+
+/// Flattened bounding-volume hierarchy over a set of primitives.
+pub struct BvhNode {
+    nodes: Vec<FlatNode>,
+}
+
+impl BvhNode {
+    pub fn aabb(&self) -> Aabb {
+        self.nodes[0].aabb
+    }
+
+    /// Walk the tree near-child-first, invoking `leaf_test(start, count, t_max)
+    /// -> Option<f32>` on each leaf and pruning subtrees whose box `t_near`
+    /// exceeds the current closest hit distance.
+    pub fn traverse(&self, ro: Vec3, rd: Vec3, inv_rd: Vec3, t_min: f32, t_max: &mut f32, leaf_test: &mut impl FnMut(usize, usize, f32) -> Option<f32>) {
+        self.traverse_node(0, ro, rd, inv_rd, t_min, t_max, leaf_test);
+    }
+
+    fn traverse_node(&self, idx: usize, ro: Vec3, rd: Vec3, inv_rd: Vec3, t_min: f32, t_max: &mut f32, leaf_test: &mut impl FnMut(usize, usize, f32) -> Option<f32>) {
+        let node = &self.nodes[idx];
+        if !node.aabb.hit(ro, inv_rd, t_min, *t_max) {
+            return;
+        }
+        if node.count > 0 {
+            if let Some(t) = leaf_test(node.start, node.count as usize, *t_max) {
+                *t_max = (*t_max).min(t);
+            }
+            return;
+        }
+
+        let left = idx + 1;
+        let right = node.right_child;
+        // Descend near child first using the ray direction's sign on the split axis.
+        if Aabb::axis(rd, node.split_axis) >= 0.0 {
+            self.traverse_node(left, ro, rd, inv_rd, t_min, t_max, leaf_test);
+            self.traverse_node(right, ro, rd, inv_rd, t_min, t_max, leaf_test);
+        } else {
+            self.traverse_node(right, ro, rd, inv_rd, t_min, t_max, leaf_test);
+            self.traverse_node(left, ro, rd, inv_rd, t_min, t_max, leaf_test);
+        }
+    }
+}
+
+/// Primitive counts at or below this many skip the SAH sweep and stay a leaf.
+const LEAF_THRESHOLD: usize = 4;
+/// Primitive counts below this skip the (more expensive) SAH sweep and split
+/// on the median centroid of the longest axis instead — cheap, and SAH's
+/// extra cost-accuracy buys little at such small counts.
+const SAH_THRESHOLD: usize = 16;
+
+/// Build a BVH over `indices` (reordered in place) using `aabbs[indices[i]]`
+/// as the bounding box of primitive `i`. Callers are expected to permute
+/// their own primitive storage to match the returned index order.
+pub fn build(aabbs: &[Aabb], indices: &mut [usize]) -> BvhNode {
+    let mut nodes = Vec::new();
+    build_range(aabbs, indices, 0, indices.len(), &mut nodes);
+    BvhNode { nodes }
+}
+
+fn build_range(aabbs: &[Aabb], indices: &mut [usize], start: usize, end: usize, nodes: &mut Vec<FlatNode>) -> usize {
+    let this_index = nodes.len();
+    let count = end - start;
+    let mut bounds = Aabb::empty();
+    let mut centroid_bounds = Aabb::empty();
+    for &i in &indices[start..end] {
+        bounds = bounds.union(aabbs[i]);
+        centroid_bounds = centroid_bounds.grow(aabbs[i].centroid());
+    }
+
+    if count <= LEAF_THRESHOLD {
+        nodes.push(FlatNode { aabb: bounds, start, count: count as u32, right_child: 0, split_axis: 0 });
+        return this_index;
+    }
+
+    let (split_axis, split) = if count < SAH_THRESHOLD {
+        (centroid_bounds.longest_axis(), count / 2)
+    } else {
+        best_sah_split(aabbs, indices, start, end)
+    };
+
+    indices[start..end].sort_by(|&a, &b| {
+        let ca = Aabb::axis(aabbs[a].centroid(), split_axis);
+        let cb = Aabb::axis(aabbs[b].centroid(), split_axis);
+        ca.total_cmp(&cb)
+    });
+    let mid = start + split;
+
+    // Reserve this node's slot before recursing so its index is stable while
+    // the left/right subtrees (and their own descendants) are appended.
+    nodes.push(FlatNode { aabb: bounds, start: 0, count: 0, right_child: 0, split_axis });
+    build_range(aabbs, indices, start, mid, nodes);
+    let right_child = build_range(aabbs, indices, mid, end, nodes);
+    nodes[this_index].right_child = right_child;
+
+    this_index
+}
+
+/// Sweeps centroids on each of the 3 axes, evaluating the SAH split cost
+/// `area(left)*count(left) + area(right)*count(right)` at every split
+/// position, and returns the `(axis, split)` pair whose split minimizes that
+/// cost, where `split` is an index into `[start, end)` relative to `start`.
+fn best_sah_split(aabbs: &[Aabb], indices: &mut [usize], start: usize, end: usize) -> (usize, usize) {
+    let count = end - start;
+    let mut best_axis = 0;
+    let mut best_split = count / 2;
+    let mut best_cost = f32::INFINITY;
+
+    for axis in 0..3 {
+        indices[start..end].sort_by(|&a, &b| {
+            let ca = Aabb::axis(aabbs[a].centroid(), axis);
+            let cb = Aabb::axis(aabbs[b].centroid(), axis);
+            ca.total_cmp(&cb)
+        });
+
+        let mut prefix_area = vec![0.0f32; count];
+        let mut running = Aabb::empty();
+        for i in 0..count {
+            running = running.union(aabbs[indices[start + i]]);
+            prefix_area[i] = running.surface_area();
+        }
+        let mut suffix_area = vec![0.0f32; count];
+        running = Aabb::empty();
+        for i in (0..count).rev() {
+            running = running.union(aabbs[indices[start + i]]);
+            suffix_area[i] = running.surface_area();
+        }
+
+        for split in 1..count {
+            let cost = prefix_area[split - 1] * split as f32 + suffix_area[split] * (count - split) as f32;
+            if cost < best_cost {
+                best_cost = cost;
+                best_axis = axis;
+                best_split = split;
+            }
+        }
+    }
+
+    (best_axis, best_split)
+}
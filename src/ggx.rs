@@ -10,36 +10,69 @@ pub fn fresnel_schlick(cos_theta:f32, f0:Vec3)->Vec3 {
 pub fn d_term(nh:f32, a:f32)->f32 {
     let a2=a*a; a2 / (PI*((nh*nh*(a2-1.0)+1.0).powi(2)))
 }
-pub fn g_term(nv:f32,nl:f32,a:f32)->f32 {
+/// Schlick-GGX masking term for a single direction; `g_term` and
+/// [`sample_ggx_vndf`]'s PDF both build on this.
+pub fn g1_term(nv: f32, a: f32) -> f32 {
     let k = a*a/2.0; // Approximation for G smith correlated
-    let g1 = nv/(nv*(1.0-k)+k);
-    let g2 = nl/(nl*(1.0-k)+k);
-    g1*g2
+    nv/(nv*(1.0-k)+k)
+}
+
+pub fn g_term(nv:f32,nl:f32,a:f32)->f32 {
+    g1_term(nv, a) * g1_term(nl, a)
 }
 
-pub fn sample_ggx_h(n: Vec3, roughness: f32, rng: &mut impl Rng) -> Vec3 {
+/// Samples a half-vector from the distribution of GGX normals *visible*
+/// from `v` (Heitz 2018), instead of sampling the full NDF — this puts no
+/// probability mass on back-facing microfacets `v` could never see, which
+/// is where full-NDF sampling wastes rough-metal samples.
+///
+/// Returns `(h, pdf)` with `pdf` already converted from the half-vector
+/// measure to the reflected-direction measure (the `/ (4*dot(v,h))`
+/// Jacobian), so a caller folding `h` into `reflect(-v, h)` can use it
+/// directly as `pdf(l)` in a `brdf * cos / pdf` Monte Carlo estimator.
+pub fn sample_ggx_vndf(v: Vec3, n: Vec3, roughness: f32, rng: &mut impl Rng) -> (Vec3, f32) {
     let a = roughness * roughness;
-    let a2 = a * a;
 
+    // World -> tangent frame around n (z-up).
+    let w = n;
+    let u = n.any_orthonormal().normalize();
+    let t = w.cross(u);
+    let v_local = Vec3(v.dot(u), v.dot(t), v.dot(w));
+
+    // Stretch the view direction into the hemisphere configuration.
+    let vh = Vec3(a * v_local.0, a * v_local.1, v_local.2).normalize();
+
+    // Orthonormal basis around vh.
+    let t1 = if vh.2 < 0.999 {
+        Vec3(0.0, 0.0, 1.0).cross(vh).normalize()
+    } else {
+        Vec3(1.0, 0.0, 0.0)
+    };
+    let t2 = vh.cross(t1);
+
+    // Sample a point on the projected hemisphere disk.
     let r1: f32 = rng.r#gen();
     let r2: f32 = rng.r#gen();
+    let r = r1.sqrt();
+    let phi = 2.0 * PI * r2;
+    let p1 = r * phi.cos();
+    let p2_disk = r * phi.sin();
+    let s = 0.5 * (1.0 + vh.2);
+    let p2 = (1.0 - s) * (1.0 - p1 * p1).max(0.0).sqrt() + s * p2_disk;
 
-    let phi = 2.0 * PI * r1;
-    let cos_theta = ((1.0 - r2) / (1.0 + (a2 - 1.0) * r2)).sqrt();
-    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let nh = t1 * p1 + t2 * p2 + vh * (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
 
-    // vector in tangent space
-    let h_tangent = Vec3(
-        phi.cos() * sin_theta,
-        phi.sin() * sin_theta,
-        cos_theta,
-    );
+    // Un-stretch back to the ellipsoid configuration to get the half-vector,
+    // in tangent space then rotated into world space.
+    let h_local = Vec3(a * nh.0, a * nh.1, nh.2.max(0.0)).normalize();
+    let h = u * h_local.0 + t * h_local.1 + w * h_local.2;
 
-    // create orthonormal basis around normal n
-    let w = n;
-    let u = n.any_orthonormal().normalize();
-    let v = w.cross(u);
+    let n_dot_v = n.dot(v).max(1e-4);
+    let n_dot_h = n.dot(h).max(1e-4);
+    let v_dot_h = v.dot(h).max(1e-4);
+    let d = d_term(n_dot_h, a);
+    let g1 = g1_term(n_dot_v, a);
+    let pdf = d * g1 * v_dot_h / n_dot_v / (4.0 * v_dot_h);
 
-    // transform from tangent space to world space
-    u * h_tangent.0 + v * h_tangent.1 + w * h_tangent.2
-}
\ No newline at end of file
+    (h, pdf)
+}
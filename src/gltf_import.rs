@@ -0,0 +1,228 @@
+//! Loads a [`Scene`] from a glTF 2.0 asset (`.gltf` or `.glb`), as an
+//! alternative to the native `scene.json` format in [`crate::scene`].
+//!
+//! Only the subset of glTF this renderer can actually use is translated:
+//! mesh primitives become [`Mesh`] objects, `pbrMetallicRoughness` becomes
+//! [`Material`], and the first camera in the asset (if any) becomes
+//! [`CameraJson`]. Render settings (resolution, sample count) and the
+//! camera's aperture/shutter have no glTF equivalent, so they fall back to
+//! fixed defaults; lights and textures are not imported.
+
+use crate::algebra::Vec3;
+use crate::bvh::{self, Aabb};
+use crate::material::{Material, MaterialMode};
+use crate::mesh::{Mesh, Triangle};
+use crate::object::Object;
+use crate::scene::{CameraJson, FilterJson, RenderJson, Scene, TonemapJson};
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 600;
+const DEFAULT_SAMPLES: u32 = 64;
+
+fn default_material() -> Material {
+    Material {
+        color: Vec3(1.0, 0.0, 1.0),
+        metallic: 0.0,
+        roughness: 1.0,
+        ior: 1.0,
+        volume_density: 0.0,
+        volume_anisotropy: 0.0,
+        emission: Vec3(0.0, 0.0, 0.0),
+        light_sampled: false,
+        mode: MaterialMode::Diffuse,
+        albedo_texture: None,
+    }
+}
+
+fn convert_material(mat: gltf::Material) -> Material {
+    let pbr = mat.pbr_metallic_roughness();
+    let [r, g, b, _a] = pbr.base_color_factor();
+    let emission = mat.emissive_factor();
+    let mode = if pbr.metallic_factor() > 0.5 {
+        MaterialMode::Mirror
+    } else {
+        MaterialMode::Diffuse
+    };
+    Material {
+        color: Vec3(r, g, b),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        ior: 1.5,
+        volume_density: 0.0,
+        volume_anisotropy: 0.0,
+        emission: Vec3(emission[0], emission[1], emission[2]),
+        light_sampled: false,
+        mode,
+        albedo_texture: None,
+    }
+}
+
+/// Builds [`Object::Mesh`]es out of every primitive in `node`'s mesh (if
+/// any), recursing into children with `transform` accumulated along the
+/// way. glTF nodes form a scene graph; the renderer only has a flat object
+/// list, so the hierarchy is flattened here.
+fn collect_meshes(
+    node: &gltf::Node,
+    transform: [[f32; 4]; 4],
+    buffers: &[gltf::buffer::Data],
+    objects: &mut Vec<Object>,
+) {
+    let transform = mat4_mul(transform, node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        for (i, primitive) in mesh.primitives().enumerate() {
+            let reader = primitive.reader(|b| Some(&buffers[b.index()]));
+            let positions: Vec<Vec3> = match reader.read_positions() {
+                Some(iter) => iter.map(|p| transform_point(transform, p.into())).collect(),
+                None => continue,
+            };
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(iter) => iter.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+            let normals: Option<Vec<Vec3>> = reader
+                .read_normals()
+                .map(|iter| iter.map(|n| transform_normal(transform, n.into())).collect());
+
+            let material = primitive
+                .material()
+                .index()
+                .map(|_| convert_material(primitive.material()))
+                .unwrap_or_else(default_material);
+
+            let mut triangles = Vec::with_capacity(indices.len() / 3);
+            for tri in indices.chunks_exact(3) {
+                let [i0, i1, i2] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+                let v0 = positions[i0];
+                let v1 = positions[i1];
+                let v2 = positions[i2];
+                let normal = (v1 - v0).cross(v2 - v0).normalize();
+                let vertex_normals = normals.as_ref().map(|n| [n[i0], n[i1], n[i2]]);
+                triangles.push(Triangle { v0, v1, v2, normal, vertex_normals, vertex_uvs: None });
+            }
+            if triangles.is_empty() {
+                continue;
+            }
+
+            let name = format!("{}#{}", mesh.name().unwrap_or("mesh"), i);
+            objects.push(Object::Mesh(Mesh::build(name, triangles, material, false)));
+        }
+    }
+
+    for child in node.children() {
+        collect_meshes(&child, transform, buffers, objects);
+    }
+}
+
+fn mat4_mul(a: [[f32; 4]; 4], b: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut out = [[0.0; 4]; 4];
+    for col in 0..4 {
+        for row in 0..4 {
+            out[col][row] = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    out
+}
+
+fn transform_point(m: [[f32; 4]; 4], p: [f32; 3]) -> Vec3 {
+    let x = m[0][0] * p[0] + m[1][0] * p[1] + m[2][0] * p[2] + m[3][0];
+    let y = m[0][1] * p[0] + m[1][1] * p[1] + m[2][1] * p[2] + m[3][1];
+    let z = m[0][2] * p[0] + m[1][2] * p[1] + m[2][2] * p[2] + m[3][2];
+    Vec3(x, y, z)
+}
+
+/// Transforms a direction (as opposed to [`transform_point`]) by `m`'s
+/// linear part only, ignoring translation, then renormalizes.
+fn transform_normal(m: [[f32; 4]; 4], n: [f32; 3]) -> Vec3 {
+    let x = m[0][0] * n[0] + m[1][0] * n[1] + m[2][0] * n[2];
+    let y = m[0][1] * n[0] + m[1][1] * n[1] + m[2][1] * n[2];
+    let z = m[0][2] * n[0] + m[1][2] * n[1] + m[2][2] * n[2];
+    Vec3(x, y, z).normalize()
+}
+
+/// Finds the first camera in the scene graph and converts it to
+/// [`CameraJson`]; returns `None` if `node`'s subtree has no camera (common
+/// for pure-geometry exports), and [`load`] then uses a fixed default view.
+fn find_camera(node: &gltf::Node, transform: [[f32; 4]; 4]) -> Option<CameraJson> {
+    let transform = mat4_mul(transform, node.transform().matrix());
+
+    if let Some(camera) = node.camera() {
+        let pos = transform_point(transform, [0.0, 0.0, 0.0]);
+        let forward = transform_point(transform, [0.0, 0.0, -1.0]) - pos;
+        let up = transform_point(transform, [0.0, 1.0, 0.0]) - pos;
+        let fov = match camera.projection() {
+            gltf::camera::Projection::Perspective(p) => p.yfov().to_degrees(),
+            gltf::camera::Projection::Orthographic(_) => 40.0,
+        };
+        return Some(CameraJson {
+            pos,
+            look_at: pos + forward,
+            up,
+            fov,
+            aperture: 0.0,
+            shutter0: 0.0,
+            shutter1: 0.0,
+        });
+    }
+
+    for child in node.children() {
+        if let Some(cam) = find_camera(&child, transform) {
+            return Some(cam);
+        }
+    }
+    None
+}
+
+fn default_camera() -> CameraJson {
+    CameraJson {
+        pos: Vec3(0.0, 1.0, 4.0),
+        look_at: Vec3(0.0, 0.0, 0.0),
+        up: Vec3(0.0, 1.0, 0.0),
+        fov: 40.0,
+        aperture: 0.0,
+        shutter0: 0.0,
+        shutter1: 0.0,
+    }
+}
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Loads `path` (`.gltf` or `.glb`) into a [`Scene`], building one top-level
+/// BVH over the imported objects the same way [`crate::scene::load`] does
+/// for `scene.json`.
+pub fn load(path: &str) -> Scene {
+    let (document, buffers, _images) = gltf::import(path).expect("gltf import");
+    let scene = document.default_scene().unwrap_or_else(|| document.scenes().next().expect("gltf scene"));
+
+    let mut objects = Vec::new();
+    for node in scene.nodes() {
+        collect_meshes(&node, IDENTITY, &buffers, &mut objects);
+    }
+
+    let camera = scene
+        .nodes()
+        .find_map(|node| find_camera(&node, IDENTITY))
+        .unwrap_or_else(default_camera);
+
+    let render = RenderJson {
+        width: DEFAULT_WIDTH,
+        height: DEFAULT_HEIGHT,
+        samples: DEFAULT_SAMPLES,
+        filter: FilterJson::default(),
+        tonemap: TonemapJson::default(),
+    };
+
+    let aabbs: Vec<Aabb> = objects.iter().map(Object::aabb).collect();
+    let mut indices: Vec<usize> = (0..objects.len()).collect();
+    let bvh = bvh::build(&aabbs, &mut indices);
+    let objects: Vec<Object> = indices.into_iter().map(|i| objects[i].clone()).collect();
+
+    let sky = crate::sky::Sky::Gradient { bottom: Vec3(0.0, 0.0, 0.0), top: Vec3(0.0, 0.0, 0.0) };
+
+    Scene { camera, render, objects, lights: Vec::new(), bvh, sky }
+}
@@ -1,12 +1,17 @@
 // C:\Users\Elias Stepanik\RustroverProjects\Pathtracer\src\gpu_renderer.rs
 
-use crate::{object::Object, scene::Scene};
+use crate::{algebra::Vec3, object::Object, scene::Scene, shader_builder::ShaderBuilder};
 use bytemuck::{Pod, Zeroable};
 use image::RgbaImage;
 use rand::Rng;
 use wgpu::util::DeviceExt;
 use wgpu::DeviceType;
 
+/// Compute workgroup edge length in both dimensions; kept in one place so
+/// the shader's `@workgroup_size` and the dispatch's `(width + N - 1) / N`
+/// tiling can't drift apart.
+pub(crate) const WORKGROUP_SIZE: u32 = 8;
+
 // The public-facing function signature must now be mutable to allow updating the scene's internal state if needed.
 // For now, we only read from it, but this is good practice for future features.
 pub fn render(scene: &Scene) -> RgbaImage {
@@ -16,28 +21,32 @@ pub fn render(scene: &Scene) -> RgbaImage {
 // All structs are defined once at the top for clarity.
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct CameraUniform {
-    pos: [f32; 4],
-    forward: [f32; 4],
-    up: [f32; 4],
-    right: [f32; 4],
-    width: u32,
-    height: u32,
-    fov: f32,
-    sphere_count: u32,
-    plane_count: u32,
-    triangle_count: u32,
-    aperture: f32,
-    focus_dist: f32,
+pub(crate) struct CameraUniform {
+    pub(crate) pos: [f32; 4],
+    pub(crate) forward: [f32; 4],
+    pub(crate) up: [f32; 4],
+    pub(crate) right: [f32; 4],
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) fov: f32,
+    pub(crate) sphere_count: u32,
+    pub(crate) plane_count: u32,
+    pub(crate) triangle_count: u32,
+    pub(crate) aperture: f32,
+    pub(crate) focus_dist: f32,
+    pub(crate) light_count: u32,
+    pub(crate) _pad0: u32,
+    pub(crate) _pad1: u32,
+    pub(crate) _pad2: u32,
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct RenderParams {
-    samples_per_pixel: u32,
-    max_bounces: u32,
-    seed1: u32,
-    seed2: u32,
+pub(crate) struct RenderParams {
+    pub(crate) samples_per_pixel: u32,
+    pub(crate) max_bounces: u32,
+    pub(crate) seed1: u32,
+    pub(crate) seed2: u32,
 }
 
 fn detect_gpu_workload(adapter: &wgpu::Adapter) -> u64 {
@@ -49,27 +58,30 @@ fn detect_gpu_workload(adapter: &wgpu::Adapter) -> u64 {
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct LightUniform {
-    pos: [f32; 4],
-    intensity: [f32; 4],
-    u: [f32; 4],
-    v: [f32; 4],
+pub(crate) struct LightUniform {
+    pub(crate) pos: [f32; 4],
+    pub(crate) intensity: [f32; 4],
+    pub(crate) u: [f32; 4],
+    pub(crate) v: [f32; 4],
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct SphereData {
+pub(crate) struct SphereData {
     center: [f32; 4],
     color: [f32; 4],
     radius: f32,
     metallic: f32,
     roughness: f32,
     ior: f32,
+    volume_density: f32,
+    volume_anisotropy: f32,
+    _pad: [f32; 2],
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct PlaneData {
+pub(crate) struct PlaneData {
     point: [f32; 4],
     normal: [f32; 4],
     u: [f32; 4],
@@ -78,12 +90,14 @@ struct PlaneData {
     metallic: f32,
     roughness: f32,
     ior: f32,
-    _pad: f32,
+    volume_density: f32,
+    volume_anisotropy: f32,
+    _pad: [f32; 3],
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
-struct TriangleData {
+pub(crate) struct TriangleData {
     v0: [f32; 4],
     v1: [f32; 4],
     v2: [f32; 4],
@@ -92,7 +106,9 @@ struct TriangleData {
     metallic: f32,
     roughness: f32,
     ior: f32,
-    _pad: f32,
+    volume_density: f32,
+    volume_anisotropy: f32,
+    _pad: [f32; 3],
 }
 
 async fn render_async(scene: &Scene) -> RgbaImage {
@@ -131,7 +147,6 @@ async fn render_async(scene: &Scene) -> RgbaImage {
 
     let num_dispatches = (total_samples + samples_per_dispatch - 1) / samples_per_dispatch;
 
-    let mut accumulated_color = vec![[0.0f32; 4]; (width * height) as usize];
     let mut rng = rand::thread_rng();
 
     println!("Starting progressive render: {} dispatches of {} samples each for a total of {} samples/pixel.", num_dispatches, samples_per_dispatch, total_samples);
@@ -150,24 +165,70 @@ async fn render_async(scene: &Scene) -> RgbaImage {
         width,
         height,
         &scene.objects,
+        &scene.bvh,
     );
 
-    let light = scene.lights.get(0).expect("Scene needs at least one light");
-    let light_uniform = LightUniform {
-        pos: [light.pos.0, light.pos.1, light.pos.2, 0.0],
-        intensity: [light.intensity.0, light.intensity.1, light.intensity.2, 0.0],
-        u: [light.u.0, light.u.1, light.u.2, 0.0],
-        v: [light.v.0, light.v.1, light.v.2, 0.0],
-    };
+    // An empty `scene.lights` is fine: `create_persistent_resources` uploads
+    // a single zeroed `LightUniform` in that case and `light_count` stays 0,
+    // so the shader's selection loop never touches it.
+    let lights = light_uniforms(&scene.lights);
 
     let (spheres, planes, tris, sphere_count, plane_count, tri_count) =
         get_object_data(scene);
+    let shader_source = ShaderBuilder::new(include_str!("gpu_pathtrace.wgsl"))
+        .with_fragment("common", include_str!("shaders/common.wgsl"))
+        .with_fragment("intersect", include_str!("shaders/intersect.wgsl"))
+        .with_fragment("shading", include_str!("shaders/shading.wgsl"))
+        .with_define("VOLUMETRICS", "1")
+        .with_define("WORKGROUP_SIZE", WORKGROUP_SIZE.to_string())
+        .build();
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Pathtrace Shader"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("gpu_pathtrace.wgsl").into()),
+        source: wgpu::ShaderSource::Wgsl(shader_source.into()),
     });
     let pipeline = create_compute_pipeline(&device, &shader);
 
+    let cam = CameraUniform {
+        pos: [
+            scene.camera.pos.0,
+            scene.camera.pos.1,
+            scene.camera.pos.2,
+            0.0,
+        ],
+        forward: [forward.0, forward.1, forward.2, 0.0],
+        up: [up.0, up.1, up.2, 0.0], // Send the correct up vector
+        right: [right.0, right.1, right.2, 0.0],
+        width,
+        height,
+        fov: scene.camera.fov,
+        sphere_count,
+        plane_count,
+        triangle_count: tri_count,
+        aperture: scene.camera.aperture,
+        focus_dist,
+        light_count: lights.len() as u32,
+        _pad0: 0,
+        _pad1: 0,
+        _pad2: 0,
+    };
+
+    // The camera, lights, and geometry never change across dispatches, so
+    // their buffers and the bind group referencing them are built once;
+    // only the per-dispatch seed is rewritten in place with
+    // `queue.write_buffer`, and the accumulation buffer lives on the device
+    // for the whole render instead of being read back every pass.
+    let resources = create_persistent_resources(
+        &device,
+        &pipeline,
+        &cam,
+        &lights,
+        &spheres,
+        &planes,
+        &tris,
+        width,
+        height,
+    );
+
     // --- Progressive Render Loop ---
     for i in 0..num_dispatches {
         let params = RenderParams {
@@ -176,41 +237,7 @@ async fn render_async(scene: &Scene) -> RgbaImage {
             seed1: rng.gen(),
             seed2: rng.gen(),
         };
-
-        let cam = CameraUniform {
-            pos: [
-                scene.camera.pos.0,
-                scene.camera.pos.1,
-                scene.camera.pos.2,
-                0.0,
-            ],
-            forward: [forward.0, forward.1, forward.2, 0.0],
-            up: [up.0, up.1, up.2, 0.0], // Send the correct up vector
-            right: [right.0, right.1, right.2, 0.0],
-            width,
-            height,
-            fov: scene.camera.fov,
-            sphere_count,
-            plane_count,
-            triangle_count: tri_count,
-            aperture: scene.camera.aperture,
-            focus_dist,
-        };
-
-        // --- START: BUG FIX ---
-        // Instead of a flawed helper trait, we create the resources and hold onto
-        // the output_buffer directly.
-        let (bind_group, staging_buffer, output_buffer, output_buffer_size) =
-            create_dispatch_resources(
-                &device,
-                &pipeline,
-                &cam,
-                &params,
-                &light_uniform,
-                &spheres,
-                &planes,
-                &tris,
-            );
+        queue.write_buffer(&resources.params_buffer, 0, bytemuck::bytes_of(&params));
 
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Encoder"),
@@ -220,52 +247,109 @@ async fn render_async(scene: &Scene) -> RgbaImage {
                 label: Some("Compute Pass"),
             });
             cpass.set_pipeline(&pipeline);
-            cpass.set_bind_group(0, &bind_group, &[]);
-            cpass.dispatch_workgroups((width + 7) / 8, (height + 7) / 8, 1);
+            cpass.set_bind_group(0, &resources.bind_group, &[]);
+            cpass.dispatch_workgroups(
+                (width + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+                (height + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+                1,
+            );
         }
-        // Now we use our direct reference to the output_buffer.
-        encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, output_buffer_size);
-        // --- END: BUG FIX ---
-
         queue.submit(Some(encoder.finish()));
-
-        let buffer_slice = staging_buffer.slice(..);
-        let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |v| tx.send(v).unwrap());
-        device.poll(wgpu::Maintain::Wait);
-        rx.receive().await.unwrap().expect("map failed");
-
-        let data = buffer_slice.get_mapped_range();
-        let pixels: &[[f32; 4]] = bytemuck::cast_slice(&data);
-        for (j, pixel_color) in pixels.iter().enumerate() {
-            accumulated_color[j][0] += pixel_color[0];
-            accumulated_color[j][1] += pixel_color[1];
-            accumulated_color[j][2] += pixel_color[2];
-        }
-        drop(data);
-        staging_buffer.unmap();
         println!("Dispatch {}/{} complete.", i + 1, num_dispatches);
     }
 
+    // Read the accumulation buffer back exactly once, now that every
+    // dispatch has added its samples into it on the device.
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(
+        &resources.output_buffer,
+        0,
+        &resources.staging_buffer,
+        0,
+        resources.output_buffer_size,
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = resources.staging_buffer.slice(..);
+    let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |v| tx.send(v).unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    rx.receive().await.unwrap().expect("map failed");
+
+    let data = buffer_slice.get_mapped_range();
+    let pixels: &[[f32; 4]] = bytemuck::cast_slice(&data);
+    let accumulated_color: Vec<[f32; 4]> = pixels.to_vec();
+    drop(data);
+    resources.staging_buffer.unmap();
+
     // --- Final Image Creation ---
+    let tonemapping = scene.render.tonemap.resolve();
     let mut img = RgbaImage::new(width, height);
     for (i, pixel_data) in accumulated_color.iter().enumerate() {
         let x = (i as u32) % width;
         let y = height - 1 - (i as u32) / width;
-        let avg_r = pixel_data[0] / total_samples as f32;
-        let avg_g = pixel_data[1] / total_samples as f32;
-        let avg_b = pixel_data[2] / total_samples as f32;
-        let tonemapped = crate::tonemap::aces_film(crate::algebra::Vec3(avg_r, avg_g, avg_b));
-        let r = (tonemapped.0.powf(1.0 / 2.2) * 255.0).min(255.0) as u8;
-        let g = (tonemapped.1.powf(1.0 / 2.2) * 255.0).min(255.0) as u8;
-        let b = (tonemapped.2.powf(1.0 / 2.2) * 255.0).min(255.0) as u8;
-        img.put_pixel(x, y, image::Rgba([r, g, b, 255]));
+        img.put_pixel(x, y, image::Rgba(tonemap_pixel(*pixel_data, total_samples, &tonemapping)));
     }
     img
 }
 
+/// Averages an accumulated `(r, g, b, unused)` pixel over `total_samples`
+/// and runs it through `tm`, the same [`crate::tonemap::ToneMapping`]
+/// pipeline the CPU renderer uses, so the batch renderer and the live
+/// viewport agree on what a pixel looks like on screen.
+pub(crate) fn tonemap_pixel(accum: [f32; 4], total_samples: u32, tm: &crate::tonemap::ToneMapping) -> [u8; 4] {
+    let avg = Vec3(
+        accum[0] / total_samples as f32,
+        accum[1] / total_samples as f32,
+        accum[2] / total_samples as f32,
+    );
+    let mapped = tm.apply(avg);
+    [
+        (mapped.0 * 255.0).min(255.0) as u8,
+        (mapped.1 * 255.0).min(255.0) as u8,
+        (mapped.2 * 255.0).min(255.0) as u8,
+        255,
+    ]
+}
+
+/// Converts every light in the scene into the GPU's uniform layout. The
+/// shader only understands rectangular area lights directly, so point/spot/
+/// directional lights are approximated as zero-area lights at their
+/// position (a directional light is pushed far away along its direction so
+/// the inverse-square falloff is negligible over the scene's extent).
+pub(crate) fn light_uniforms(lights: &[crate::light::Light]) -> Vec<LightUniform> {
+    lights
+        .iter()
+        .map(|light| {
+            let (pos, intensity, u, v) = match *light {
+                crate::light::Light::Area { pos, u, v, intensity } => (pos, intensity, u, v),
+                crate::light::Light::Point { pos, intensity, .. } => {
+                    (pos, intensity, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 0.0))
+                }
+                crate::light::Light::Spot { pos, intensity, .. } => {
+                    (pos, intensity, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 0.0))
+                }
+                crate::light::Light::Directional { dir, intensity } => {
+                    (dir.scale(-1e4), intensity, Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 0.0))
+                }
+            };
+            LightUniform {
+                pos: [pos.0, pos.1, pos.2, 0.0],
+                intensity: [intensity.0, intensity.1, intensity.2, 0.0],
+                u: [u.0, u.1, u.2, 0.0],
+                v: [v.0, v.1, v.2, 0.0],
+            }
+        })
+        .collect()
+}
+
 // Helper function to keep the main loop cleaner by setting up buffers.
-fn get_object_data(scene: &Scene) -> (
+// Sized to the scene's actual object counts rather than a fixed cap, so a
+// scene isn't silently truncated once it outgrows some arbitrary ceiling —
+// the real limit is VRAM, enforced by the driver rather than this code.
+pub(crate) fn get_object_data(scene: &Scene) -> (
     Vec<SphereData>,
     Vec<PlaneData>,
     Vec<TriangleData>,
@@ -273,17 +357,13 @@ fn get_object_data(scene: &Scene) -> (
     u32,
     u32,
 ) {
-    const MAX_SPHERES: usize = 32;
-    const MAX_PLANES: usize = 32;
-    const MAX_TRIS: usize = 8192;
-    let mut spheres = vec![SphereData::zeroed(); MAX_SPHERES];
-    let mut planes = vec![PlaneData::zeroed(); MAX_PLANES];
-    let mut tris = vec![TriangleData::zeroed(); MAX_TRIS];
-    let (mut scount, mut pcount, mut tcount) = (0, 0, 0);
+    let mut spheres = Vec::new();
+    let mut planes = Vec::new();
+    let mut tris = Vec::new();
     for obj in &scene.objects {
         match obj {
-            Object::Sphere(s) if scount < MAX_SPHERES => {
-                spheres[scount] = SphereData {
+            Object::Sphere(s) => {
+                spheres.push(SphereData {
                     center: [s.center.0, s.center.1, s.center.2, 0.0],
                     color: [
                         s.material.color.0,
@@ -295,11 +375,13 @@ fn get_object_data(scene: &Scene) -> (
                     metallic: s.material.metallic,
                     roughness: s.material.roughness,
                     ior: s.material.ior,
-                };
-                scount += 1;
+                    volume_density: s.material.volume_density,
+                    volume_anisotropy: s.material.volume_anisotropy,
+                    _pad: [0.0; 2],
+                });
             }
-            Object::Plane(p) if pcount < MAX_PLANES => {
-                planes[pcount] = PlaneData {
+            Object::Rect(p) => {
+                planes.push(PlaneData {
                     point: [p.point.0, p.point.1, p.point.2, 0.0],
                     normal: [p.normal.0, p.normal.1, p.normal.2, 0.0],
                     u: [p.u.0, p.u.1, p.u.2, 0.0],
@@ -313,16 +395,42 @@ fn get_object_data(scene: &Scene) -> (
                     metallic: p.material.metallic,
                     roughness: p.material.roughness,
                     ior: p.material.ior,
-                    _pad: 0.0,
-                };
-                pcount += 1;
+                    volume_density: p.material.volume_density,
+                    volume_anisotropy: p.material.volume_anisotropy,
+                    _pad: [0.0; 3],
+                });
+            }
+            Object::InfinitePlane(p) => {
+                // The GPU plane shader only understands bounded rectangles,
+                // so approximate the infinite plane as a huge one, the same
+                // way `InfinitePlane::aabb` stands in for a true infinity on
+                // the CPU side.
+                let tangent0 = p.normal.any_orthonormal().normalize();
+                let tangent1 = p.normal.cross(tangent0);
+                let u = tangent0.scale(1.0e6);
+                let v = tangent1.scale(1.0e6);
+                planes.push(PlaneData {
+                    point: [p.point.0, p.point.1, p.point.2, 0.0],
+                    normal: [p.normal.0, p.normal.1, p.normal.2, 0.0],
+                    u: [u.0, u.1, u.2, 0.0],
+                    v: [v.0, v.1, v.2, 0.0],
+                    color: [
+                        p.material.color.0,
+                        p.material.color.1,
+                        p.material.color.2,
+                        0.0,
+                    ],
+                    metallic: p.material.metallic,
+                    roughness: p.material.roughness,
+                    ior: p.material.ior,
+                    volume_density: p.material.volume_density,
+                    volume_anisotropy: p.material.volume_anisotropy,
+                    _pad: [0.0; 3],
+                });
             }
             Object::Mesh(m) => {
                 for tri in &m.triangles {
-                    if tcount >= MAX_TRIS {
-                        break;
-                    }
-                    tris[tcount] = TriangleData {
+                    tris.push(TriangleData {
                         v0: [tri.v0.0, tri.v0.1, tri.v0.2, 0.0],
                         v1: [tri.v1.0, tri.v1.1, tri.v1.2, 0.0],
                         v2: [tri.v2.0, tri.v2.1, tri.v2.2, 0.0],
@@ -336,18 +444,19 @@ fn get_object_data(scene: &Scene) -> (
                         metallic: m.material.metallic,
                         roughness: m.material.roughness,
                         ior: m.material.ior,
-                        _pad: 0.0,
-                    };
-                    tcount += 1;
+                        volume_density: m.material.volume_density,
+                        volume_anisotropy: m.material.volume_anisotropy,
+                        _pad: [0.0; 3],
+                    });
                 }
             }
-            _ => {}
         }
     }
-    spheres.truncate(scount);
-    planes.truncate(pcount);
-    tris.truncate(tcount);
+    let (scount, pcount, tcount) = (spheres.len(), planes.len(), tris.len());
 
+    // wgpu storage bindings can't bind a zero-length buffer, so an empty
+    // category gets a single zeroed element; `*_count` in `CameraUniform`
+    // still reports zero, so the shader's loops never touch it.
     if spheres.is_empty() {
         spheres.push(SphereData::zeroed());
     }
@@ -368,7 +477,7 @@ fn get_object_data(scene: &Scene) -> (
 }
 
 // Helper to create the compute pipeline
-fn create_compute_pipeline(
+pub(crate) fn create_compute_pipeline(
     device: &wgpu::Device,
     shader: &wgpu::ShaderModule,
 ) -> wgpu::ComputePipeline {
@@ -399,7 +508,7 @@ fn create_compute_pipeline(
                 binding: 2,
                 visibility: wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
                     has_dynamic_offset: false,
                     min_binding_size: None,
                 },
@@ -460,31 +569,58 @@ fn create_compute_pipeline(
     })
 }
 
-// Helper to create resources for a single dispatch
-fn create_dispatch_resources(
+/// Buffers that live for the whole render: the camera, lights and geometry
+/// never change between dispatches, and every dispatch adds into the same
+/// on-device accumulation buffer, so they (and the bind group referencing
+/// them) are built once instead of being re-uploaded every pass.
+/// `params_buffer` is rewritten in place before each dispatch via
+/// `queue.write_buffer` to carry that dispatch's seeds.
+pub(crate) struct PersistentResources {
+    pub(crate) bind_group: wgpu::BindGroup,
+    pub(crate) params_buffer: wgpu::Buffer,
+    pub(crate) output_buffer: wgpu::Buffer,
+    pub(crate) staging_buffer: wgpu::Buffer,
+    pub(crate) output_buffer_size: u64,
+}
+
+// Helper to create the buffers and bind group shared by every dispatch.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_persistent_resources(
     device: &wgpu::Device,
     pipeline: &wgpu::ComputePipeline,
     cam: &CameraUniform,
-    params: &RenderParams,
-    light_uniform: &LightUniform,
+    lights: &[LightUniform],
     spheres: &[SphereData],
     planes: &[PlaneData],
     triangles: &[TriangleData],
-) -> (wgpu::BindGroup, wgpu::Buffer, wgpu::Buffer, u64) {
+    width: u32,
+    height: u32,
+) -> PersistentResources {
     let cam_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Camera"),
         contents: bytemuck::bytes_of(cam),
         usage: wgpu::BufferUsages::UNIFORM,
     });
-    let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    // Rewritten in full before every dispatch via `queue.write_buffer`
+    // (fresh seeds each pass), never recreated.
+    let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("Params"),
-        contents: bytemuck::bytes_of(params),
-        usage: wgpu::BufferUsages::UNIFORM,
+        size: std::mem::size_of::<RenderParams>() as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
     });
+    // Like the geometry storage buffers below, wgpu can't bind a
+    // zero-length buffer, so an empty light list still uploads one zeroed
+    // light; `CameraUniform::light_count` stays 0 either way, so the
+    // shader's selection loop never touches it.
+    let mut light_data = lights.to_vec();
+    if light_data.is_empty() {
+        light_data.push(LightUniform::zeroed());
+    }
     let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Light"),
-        contents: bytemuck::bytes_of(light_uniform),
-        usage: wgpu::BufferUsages::UNIFORM,
+        label: Some("Lights"),
+        contents: bytemuck::cast_slice(&light_data),
+        usage: wgpu::BufferUsages::STORAGE,
     });
     let sphere_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Spheres"),
@@ -501,12 +637,12 @@ fn create_dispatch_resources(
         contents: bytemuck::cast_slice(triangles),
         usage: wgpu::BufferUsages::STORAGE,
     });
-    let output_buffer_size = (cam.width * cam.height * 16) as wgpu::BufferAddress;
-    let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+    let output_buffer_size = (width * height * 16) as wgpu::BufferAddress;
+    // Zeroed so the shader's additive accumulation starts from nothing.
+    let output_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Output"),
-        size: output_buffer_size,
+        contents: &vec![0u8; output_buffer_size as usize],
         usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
-        mapped_at_creation: false,
     });
     let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("Staging"),
@@ -550,10 +686,11 @@ fn create_dispatch_resources(
         ],
     });
 
-    (
+    PersistentResources {
         bind_group,
-        staging_buffer,
+        params_buffer,
         output_buffer,
+        staging_buffer,
         output_buffer_size,
-    )
+    }
 }
@@ -1,9 +1,39 @@
 use crate::algebra::Vec3;
 
+/// A scene light. `Area` is the original rectangular light; `Point`, `Spot`
+/// and `Directional` bring the renderer's light model in line with the
+/// EEVEE `LightData` set (position/radius soft shadows, spot cone size and
+/// blend, directional constant irradiance).
 #[derive(Clone, Copy)]
-pub struct Light {
-    pub pos: Vec3,
-    pub u:   Vec3,
-    pub v:   Vec3,
-    pub intensity: Vec3,
-}
\ No newline at end of file
+pub enum Light {
+    Point {
+        pos: Vec3,
+        intensity: Vec3,
+        /// Soft-shadow radius; 0 for a perfect point light.
+        radius: f32,
+        /// Distance past which the light's contribution is forced to zero,
+        /// bounding shadow-ray work. `None` means unbounded.
+        influence: Option<f32>,
+    },
+    Spot {
+        pos: Vec3,
+        dir: Vec3,
+        intensity: Vec3,
+        /// Cosine of the full cone half-angle; outside this, no light.
+        cos_size: f32,
+        /// Cosine of the inner (fully-lit) cone half-angle; the contribution
+        /// is smoothstep-blended between `cos_size` and `cos_blend`.
+        cos_blend: f32,
+        influence: Option<f32>,
+    },
+    Directional {
+        dir: Vec3,
+        intensity: Vec3,
+    },
+    Area {
+        pos: Vec3,
+        u: Vec3,
+        v: Vec3,
+        intensity: Vec3,
+    },
+}
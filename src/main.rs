@@ -9,27 +9,40 @@ mod renderer;
 mod gpu_renderer;
 mod plane;
 mod sphere;
+mod mesh;
+mod texture;
+mod bvh;
+mod gltf_import;
+mod tile;
+mod film;
+mod sky;
+mod shader_builder;
+mod viewport;
 
 use std::{env, fs};
 use std::path::Path;
-use crate::{
-    renderer::render_image_name,
-    algebra::{sample_disk, Vec3},
-};
-use image::{Rgb, RgbImage, RgbaImage};
+use crate::renderer::render_image_name;
+use image::RgbaImage;
 use indicatif::{ProgressBar, ProgressStyle};
-use rand::thread_rng;
-use rayon::prelude::*;
 use std::sync::Arc;
 use crate::scene::load;
 
 const MAX_DEPTH: u32 = 12;
 const MAX_GLASS_BOUNCES: u32 = 8;
 
+/// How many samples each tile accumulates per pass. Small enough that the
+/// preview after a pass is fast, large enough that per-pass overhead (the
+/// mean flush over the whole image) stays a small fraction of the work.
+const SAMPLES_PER_PASS: u32 = 4;
+
+/// Where the in-progress preview is (re)written after each pass.
+const PREVIEW_PATH: &str = "renders/preview.png";
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     let quiet_mode = args.contains(&"--quiet".to_string()) || args.contains(&"-q".to_string());
     let gpu_mode = args.contains(&"--gpu".to_string());
+    let viewport_mode = args.contains(&"--viewport".to_string());
 
     // ── parse JSON ────────────────────────────────────────────────────────
     let scene = load("scene.json");
@@ -39,6 +52,8 @@ fn main() {
     let samples   = scene.render.samples;
     let aperture  = scene.camera.aperture;
     let fov_rad   = scene.camera.fov.to_radians();
+    let shutter0  = scene.camera.shutter0;
+    let shutter1  = scene.camera.shutter1;
 
     // camera basis
     let aspect = width as f32 / height as f32;
@@ -53,7 +68,7 @@ fn main() {
     // autofocus
     let focus = renderer::autofocus(
         pos, right, real_up, forward,
-        aspect, scale, width, height, &scene.objects);
+        aspect, scale, width, height, &scene.objects, &scene.bvh);
 
 
     // ── dump debug info ────────────────────────────────────────────────────
@@ -73,17 +88,46 @@ fn main() {
                 println!(" [{}] Sphere '{}' {{ center: {:?}, radius: {:.4}, mat_color: {:?} }}",
                          i, s.name, s.center, s.radius, s.material.color);
             }
-            crate::object::Object::Plane(p) => {
-                println!(" [{}] Plane '{}' {{ point: {:?}, normal: {:?}, mat_color: {:?} }}",
+            crate::object::Object::Rect(p) => {
+                println!(" [{}] Rect '{}' {{ point: {:?}, normal: {:?}, mat_color: {:?} }}",
                          i, p.name, p.point, p.normal, p.material.color);
             }
+            crate::object::Object::InfinitePlane(p) => {
+                println!(" [{}] InfinitePlane '{}' {{ point: {:?}, normal: {:?}, mat_color: {:?} }}",
+                         i, p.name, p.point, p.normal, p.material.color);
+            }
+            crate::object::Object::Mesh(m) => {
+                println!(" [{}] Mesh '{}' {{ triangles: {}, mat_color: {:?} }}",
+                         i, m.name, m.triangles.len(), m.material.color);
+            }
         }
     }
 
     println!("\n=== LIGHTS ({}) ===", scene.lights.len());
     for (i, l) in scene.lights.iter().enumerate() {
-        println!(" [{}] Light {{ pos: {:?}, u: {:?}, v: {:?}, intensity: {:?} }}",
-                 i, l.pos, l.u, l.v, l.intensity);
+        match l {
+            crate::light::Light::Point { pos, intensity, radius, influence } => {
+                println!(" [{}] Point {{ pos: {:?}, intensity: {:?}, radius: {:.3}, influence: {:?} }}",
+                         i, pos, intensity, radius, influence);
+            }
+            crate::light::Light::Spot { pos, dir, intensity, .. } => {
+                println!(" [{}] Spot {{ pos: {:?}, dir: {:?}, intensity: {:?} }}",
+                         i, pos, dir, intensity);
+            }
+            crate::light::Light::Directional { dir, intensity } => {
+                println!(" [{}] Directional {{ dir: {:?}, intensity: {:?} }}", i, dir, intensity);
+            }
+            crate::light::Light::Area { pos, u, v, intensity } => {
+                println!(" [{}] Area {{ pos: {:?}, u: {:?}, v: {:?}, intensity: {:?} }}",
+                         i, pos, u, v, intensity);
+            }
+        }
+    }
+
+    if viewport_mode {
+        println!("Opening interactive viewport...");
+        viewport::run(scene);
+        return;
     }
 
     if gpu_mode {
@@ -107,53 +151,73 @@ fn main() {
         return;
     }
 
-    // ── multithreaded render loop ─────────────────────────────────────────
+    // ── tiled, pass-based render loop ──────────────────────────────────────
+    let total_passes = samples.div_ceil(SAMPLES_PER_PASS);
     let bar = if !quiet_mode {
-        let pb = ProgressBar::new(height as u64);
+        let pb = ProgressBar::new(total_passes as u64);
         pb.set_style(ProgressStyle::default_bar()
-            .template("{bar:40.cyan/blue} {pos}/{len} rows | {elapsed_precise} | ETA: {eta}").unwrap());
+            .template("{bar:40.cyan/blue} {pos}/{len} passes | {elapsed_precise} | ETA: {eta}").unwrap());
         Some(pb)
     } else {
         println!("\nRendering {}x{} image with {} samples... (quiet mode)", width, height, samples);
         None
     };
 
-
     let objects = Arc::new(scene.objects);
+    let bvh     = Arc::new(scene.bvh);
     let lights  = Arc::new(scene.lights);
 
-    let mut img = RgbImage::new(width, height);
-    let rows: Vec<_> = (0..height).into_par_iter().flat_map(|y| {
+    fs::create_dir_all("renders").expect("Failed to create renders directory");
+
+    let (filter, filter_radius) = scene.render.filter.resolve();
+    let tonemapping = scene.render.tonemap.resolve();
+    let params = tile::RenderParams {
+        width, height, samples, samples_per_pass: SAMPLES_PER_PASS,
+        filter, filter_radius, tonemapping,
+        aspect, scale, cam: pos, right, up: real_up, forward, focus, aperture,
+        shutter0, shutter1,
+        objs: &objects, bvh: &bvh, lights: &lights, sky: &scene.sky,
+    };
+    let output = tile::render(&params, |_pass, samples_done, preview| {
         if let Some(b) = &bar {
             b.inc(1);
         }
-
-        let mut rng = thread_rng();
-        let mut row = Vec::with_capacity(width as usize);
-
-        for x in 0..width {
-            // --- THIS IS THE CORRECTED FUNCTION CALL ---
-            // It matches the latest signature of pixel_color in renderer.rs
-            let col = renderer::pixel_color(
-                x, y, width, height, samples, aspect, scale,
-                pos, right, real_up, forward, focus, aperture,
-                &objects, &lights, &mut rng);
-            row.push(((x, y), col));
+        // A rapidly-refining preview users can open mid-render and stop
+        // early on, instead of waiting for the whole image to finish.
+        preview.save(PREVIEW_PATH).ok();
+        if quiet_mode {
+            println!(" {samples_done}/{samples} samples");
         }
-        row
-    }).collect();
+    });
 
     if let Some(b) = bar {
         b.finish_with_message("Rendering complete");
     }
 
-    for ((x, y), rgb) in rows { img.put_pixel(x, y, Rgb(rgb)); }
     let name = render_image_name(width, height, samples, aperture, focus);
-
     if let Some(dir) = Path::new(&name).parent() {
         fs::create_dir_all(dir).expect("Failed to create renders directory");
     }
-    
-    img.save(&name).unwrap();
+
+    output.image.save(&name).unwrap();
     println!("Saved → {name}");
+
+    // Alongside the tonemapped LDR image, keep a lossless 32-bit linear
+    // master so the render can be re-graded later without re-rendering.
+    let exr_name = Path::new(&name).with_extension("exr");
+    write_exr(&exr_name, width, height, &output.linear);
+    println!("Saved → {}", exr_name.display());
+}
+
+/// Writes `linear` (row-major, `width * height` pixels) as a 32-bit float
+/// RGB EXR, bypassing tonemapping entirely — a grading master alongside the
+/// tonemapped preview/output image.
+fn write_exr(path: &Path, width: u32, height: u32, linear: &[Vec3]) {
+    use exr::prelude::*;
+
+    write_rgb_file(path, width as usize, height as usize, |x, y| {
+        let c = linear[y * width as usize + x];
+        (c.0, c.1, c.2)
+    })
+    .expect("failed to write EXR file");
 }
\ No newline at end of file
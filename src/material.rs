@@ -1,5 +1,17 @@
 use crate::algebra::Vec3;
 
+/// How a surface scatters light on an indirect bounce. Chosen explicitly by
+/// the scene author rather than inferred from `metallic`/`roughness`/`ior`,
+/// so "perfect mirror" and "rough glossy" don't have to be approximated by
+/// tuning those heuristically.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MaterialMode {
+    Diffuse,
+    Glossy { specular_exponent: f32 },
+    Mirror,
+    Dielectric,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Material {
     pub color: Vec3,
@@ -8,4 +20,32 @@ pub struct Material {
     pub ior: f32,
     pub volume_density: f32,
     pub volume_anisotropy: f32,
+    /// Radiance emitted by the surface itself, letting any object act as a
+    /// light. Zero for non-emissive materials.
+    pub emission: Vec3,
+    /// True when `emission` is already represented as an implicit `Light`
+    /// that `direct_light_sample` next-event-estimates (see the emissive
+    /// `Rect` collection in `scene::load`). `trace` only adds `emission`
+    /// directly on a camera/specular ray for these, the same as the sky and
+    /// the explicit `lights` array, so a diffuse/glossy bounce doesn't
+    /// double-count what NEE already picked up at the previous hit.
+    pub light_sampled: bool,
+    pub mode: MaterialMode,
+    /// Image sampled in place of `color` wherever the hit geometry carries
+    /// UVs; `None` uses the flat `color` everywhere.
+    pub albedo_texture: Option<&'static crate::texture::Texture>,
+}
+
+impl Material {
+    /// Returns a copy of this material with `color` replaced by
+    /// `albedo_texture` sampled at `uv`, when both are present; otherwise
+    /// an identical copy. Lets `hit` surface a spatially-varying albedo
+    /// without changing the `(t, normal, Material)` shape every caller
+    /// already expects.
+    pub fn with_albedo_at(&self, uv: Option<(f32, f32)>) -> Material {
+        match (self.albedo_texture, uv) {
+            (Some(tex), Some((u, v))) => Material { color: tex.sample(u, v), ..*self },
+            _ => *self,
+        }
+    }
 }
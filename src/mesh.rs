@@ -1,11 +1,33 @@
-use crate::{algebra::Vec3, material::Material};
+use crate::{algebra::Vec3, material::{Material, MaterialMode}};
+use crate::bvh::{self, Aabb, BvhNode};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
 
 #[derive(Clone)]
 pub struct Triangle {
     pub v0: Vec3,
     pub v1: Vec3,
     pub v2: Vec3,
+    /// Flat geometric face normal, `(v1-v0) x (v2-v0)` normalized. Always
+    /// present so ray-origin offsetting and meshes without vertex normals
+    /// have a normal to shade with.
     pub normal: Vec3,
+    /// Per-vertex normals for smooth (Phong/Gouraud-style) shading,
+    /// `(n0, n1, n2)` matching `(v0, v1, v2)`; `None` for meshes whose
+    /// source format didn't supply them, in which case `hit` falls back to
+    /// the flat `normal`.
+    pub vertex_normals: Option<[Vec3; 3]>,
+    /// Per-vertex texture coordinates `(uv0, uv1, uv2)` matching `(v0, v1,
+    /// v2)`; `None` for meshes whose source format didn't supply them, in
+    /// which case `hit` has no UV to sample `material.albedo_texture` with.
+    pub vertex_uvs: Option<[(f32, f32); 3]>,
+}
+
+impl Triangle {
+    fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.v0, self.v1).grow(self.v2)
+    }
 }
 
 #[derive(Clone)]
@@ -14,29 +36,64 @@ pub struct Mesh {
     pub triangles: Vec<Triangle>,
     pub material: Material,
     pub in_focus: bool,
+    bvh: std::sync::Arc<BvhNode>,
 }
 
 impl Mesh {
-    pub fn hit(&self, ro: Vec3, rd: Vec3) -> Option<(f32, Vec3, Material)> {
-        let mut closest_t = f32::INFINITY;
+    /// Builds a mesh from its triangle soup, constructing a BVH over the
+    /// triangles (reordering them into BVH-leaf order) so `hit` doesn't need
+    /// to scan every triangle per ray.
+    pub fn build(name: String, mut triangles: Vec<Triangle>, material: Material, in_focus: bool) -> Self {
+        let aabbs: Vec<Aabb> = triangles.iter().map(Triangle::aabb).collect();
+        let mut indices: Vec<usize> = (0..triangles.len()).collect();
+        let bvh = bvh::build(&aabbs, &mut indices);
+        triangles = indices.into_iter().map(|i| triangles[i].clone()).collect();
+        Self { name, triangles, material, in_focus, bvh: std::sync::Arc::new(bvh) }
+    }
+
+    /// Walks the per-mesh BVH front-to-back via [`BvhNode::traverse`],
+    /// pruning subtrees whose box entry distance exceeds the closest hit
+    /// found so far and only running [`triangle_intersect`] on leaf
+    /// triangles — this is the
+    /// acceleration structure that replaced the old brute-force per-triangle
+    /// scan.
+    pub fn hit(&self, ro: Vec3, rd: Vec3, t_min: f32, t_max: f32) -> Option<(f32, Vec3, Material)> {
+        let inv_rd = Vec3(1.0 / rd.0, 1.0 / rd.1, 1.0 / rd.2);
+        let mut closest_t = t_max;
         let mut hit_normal = Vec3(0.0, 0.0, 0.0);
-        for tri in &self.triangles {
-            if let Some(t) = triangle_intersect(tri, ro, rd) {
-                if t > 1e-4 && t < closest_t {
-                    closest_t = t;
-                    hit_normal = tri.normal;
+        let mut hit_uv: Option<(f32, f32)> = None;
+        let triangles = &self.triangles;
+        let mut bvh_t_max = t_max;
+        self.bvh.traverse(ro, rd, inv_rd, t_min, &mut bvh_t_max, &mut |start, count, local_max| {
+            let mut best: Option<f32> = None;
+            for tri in &triangles[start..start + count] {
+                if let Some((t, u, v)) = triangle_intersect(tri, ro, rd) {
+                    if t > t_min && t < local_max && t < closest_t {
+                        closest_t = t;
+                        hit_normal = shading_normal(tri, u, v);
+                        hit_uv = shading_uv(tri, u, v);
+                        best = Some(best.map_or(t, |b| b.min(t)));
+                    }
                 }
             }
-        }
-        if closest_t < f32::INFINITY {
-            Some((closest_t, hit_normal, self.material))
+            best
+        });
+        if closest_t < t_max {
+            Some((closest_t, hit_normal, self.material.with_albedo_at(hit_uv)))
         } else {
             None
         }
     }
+
+    pub fn aabb(&self) -> Aabb {
+        self.bvh.aabb()
+    }
 }
 
-fn triangle_intersect(tri: &Triangle, ro: Vec3, rd: Vec3) -> Option<f32> {
+/// Möller-Trumbore intersection, returning `(t, u, v)` on a hit; `u`/`v` are
+/// the barycentric weights of `v1`/`v2` (`w = 1 - u - v` is `v0`'s), used by
+/// [`shading_normal`] to interpolate vertex normals.
+fn triangle_intersect(tri: &Triangle, ro: Vec3, rd: Vec3) -> Option<(f32, f32, f32)> {
     let e1 = tri.v1 - tri.v0;
     let e2 = tri.v2 - tri.v0;
     let p = rd.cross(e2);
@@ -56,36 +113,195 @@ fn triangle_intersect(tri: &Triangle, ro: Vec3, rd: Vec3) -> Option<f32> {
         return None;
     }
     let t = e2.dot(q) * inv_det;
-    (t > 0.0).then_some(t)
+    (t > 0.0).then_some((t, u, v))
+}
+
+/// Shading normal at barycentric `(u, v)`: the interpolated vertex normal
+/// `(w*n0 + u*n1 + v*n2).normalize()` when `tri` has vertex normals, falling
+/// back to the flat face normal otherwise.
+fn shading_normal(tri: &Triangle, u: f32, v: f32) -> Vec3 {
+    match tri.vertex_normals {
+        Some([n0, n1, n2]) => {
+            let w = 1.0 - u - v;
+            (n0.scale(w) + n1.scale(u) + n2.scale(v)).normalize()
+        }
+        None => tri.normal,
+    }
+}
+
+/// Interpolated UV at barycentric `(u, v)`, `w*uv0 + u*uv1 + v*uv2`, when
+/// `tri` carries vertex UVs; `None` for meshes with no texture coordinates.
+fn shading_uv(tri: &Triangle, u: f32, v: f32) -> Option<(f32, f32)> {
+    let [uv0, uv1, uv2] = tri.vertex_uvs?;
+    let w = 1.0 - u - v;
+    Some((w * uv0.0 + u * uv1.0 + v * uv2.0, w * uv0.1 + u * uv1.1 + v * uv2.1))
+}
+
+/// Parses `Kd` (diffuse color), `map_Kd` (diffuse texture), `Ks`/`Ns`
+/// (specular color/exponent), `Ni` (IOR) and `Ke` (emission) out of a
+/// Wavefront `.mtl` file into this renderer's [`Material`], keyed by
+/// `newmtl` name. Classic MTL has no metallic/roughness split, so a
+/// non-zero `Ks` becomes `Glossy` mode with `Ns` converted to GGX roughness
+/// the same way shading turns a `Glossy` material's `specular_exponent`
+/// into a sample lobe: `sqrt(2/(Ns+2))`.
+fn parse_mtl(path: &Path) -> io::Result<HashMap<String, Material>> {
+    let data = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut materials = HashMap::new();
+
+    let mut name: Option<String> = None;
+    let mut kd = Vec3(1.0, 1.0, 1.0);
+    let mut ks = Vec3(0.0, 0.0, 0.0);
+    let mut ns = 0.0f32;
+    let mut ni = 1.0f32;
+    let mut ke = Vec3(0.0, 0.0, 0.0);
+    let mut map_kd: Option<String> = None;
+
+    fn finish(
+        name: Option<String>, kd: Vec3, ks: Vec3, ns: f32, ni: f32, ke: Vec3,
+        map_kd: Option<String>, base_dir: &Path, materials: &mut HashMap<String, Material>,
+    ) {
+        let Some(name) = name else { return };
+        let mode = if ks.0 > 0.0 || ks.1 > 0.0 || ks.2 > 0.0 {
+            MaterialMode::Glossy { specular_exponent: ns }
+        } else {
+            MaterialMode::Diffuse
+        };
+        let roughness = (2.0 / (ns + 2.0)).sqrt().clamp(0.01, 1.0);
+        let albedo_texture = map_kd.map(|file| crate::texture::Texture::load(&base_dir.join(file).to_string_lossy()));
+        materials.insert(name, Material {
+            color: kd, metallic: 0.0, roughness, ior: ni,
+            volume_density: 0.0, volume_anisotropy: 0.0, emission: ke, light_sampled: false, mode,
+            albedo_texture,
+        });
+    }
+
+    for line in data.lines() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("newmtl") => {
+                finish(name.take(), kd, ks, ns, ni, ke, map_kd.take(), base_dir, &mut materials);
+                name = parts.next().map(str::to_string);
+                kd = Vec3(1.0, 1.0, 1.0);
+                ks = Vec3(0.0, 0.0, 0.0);
+                ns = 0.0;
+                ni = 1.0;
+                ke = Vec3(0.0, 0.0, 0.0);
+            }
+            Some("Kd") => kd = parse_vec3(parts).unwrap_or(kd),
+            Some("Ks") => ks = parse_vec3(parts).unwrap_or(ks),
+            Some("Ke") => ke = parse_vec3(parts).unwrap_or(ke),
+            Some("Ns") => ns = parts.next().and_then(|s| s.parse().ok()).unwrap_or(ns),
+            Some("Ni") => ni = parts.next().and_then(|s| s.parse().ok()).unwrap_or(ni),
+            Some("map_Kd") => map_kd = parts.next().map(str::to_string),
+            _ => {}
+        }
+    }
+    finish(name, kd, ks, ns, ni, ke, map_kd, base_dir, &mut materials);
+    Ok(materials)
 }
 
-pub fn load_obj(path: &str) -> Vec<[Vec3; 3]> {
-    let data = std::fs::read_to_string(path).expect("obj file");
-    let mut verts = Vec::new();
-    let mut tris = Vec::new();
+fn parse_vec3<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<Vec3> {
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    Some(Vec3(x, y, z))
+}
+
+/// Parses one `f` token — `v`, `v/vt`, `v/vt/vn`, or `v//vn` — into a 0-based
+/// position index and optional 0-based texcoord/normal indices.
+fn parse_face_vertex(tok: &str) -> Option<(usize, Option<usize>, Option<usize>)> {
+    let mut parts = tok.split('/');
+    let v: usize = parts.next()?.parse().ok()?;
+    let vt = parts.next().and_then(|s| s.parse::<usize>().ok());
+    let vn = parts.next().and_then(|s| s.parse::<usize>().ok());
+    Some((v - 1, vt.map(|n| n - 1), vn.map(|n| n - 1)))
+}
+
+/// Parses a full Wavefront `.obj`: `v`/`vn`/`vt` data, `mtllib`/`usemtl`
+/// material references resolved via [`parse_mtl`], and `f` faces (fan
+/// triangulated beyond three vertices) carrying vertex normals and UVs when
+/// `vn`/`vt` are present. Each `o`/`g` group becomes its own [`Mesh`]; an
+/// unnamed leading group catches faces that precede the first group
+/// directive. Faces before the first `usemtl`/in a group with no `mtllib`
+/// material get `fallback` (typically the mesh's `mat` from scene.json).
+/// Returns an `Err` instead of panicking on a missing or unreadable file.
+pub fn load_obj(path: &str, fallback: Material) -> io::Result<Vec<Mesh>> {
+    let path = Path::new(path);
+    let data = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut texcoords: Vec<(f32, f32)> = Vec::new();
+    let mut materials: HashMap<String, Material> = HashMap::new();
+    let mut current_material = fallback;
+
+    let mut groups: Vec<(String, Vec<Triangle>)> = vec![("default".to_string(), Vec::new())];
+    let mut group_materials: Vec<Material> = vec![current_material];
 
     for line in data.lines() {
         let mut parts = line.split_whitespace();
         match parts.next() {
             Some("v") => {
-                let nums: Vec<f32> = parts.filter_map(|s| s.parse().ok()).collect();
-                if nums.len() >= 3 {
-                    verts.push(Vec3(nums[0], nums[1], nums[2]));
+                if let Some(p) = parse_vec3(parts) {
+                    positions.push(p);
                 }
             }
+            Some("vn") => {
+                if let Some(n) = parse_vec3(parts) {
+                    normals.push(n);
+                }
+            }
+            Some("vt") => {
+                if let Some(u) = parts.next().and_then(|s| s.parse().ok()) {
+                    let v = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0.0);
+                    texcoords.push((u, v));
+                }
+            }
+            Some("mtllib") => {
+                if let Some(file) = parts.next() {
+                    if let Ok(lib) = parse_mtl(&base_dir.join(file)) {
+                        materials.extend(lib);
+                    }
+                    // A missing/unreadable .mtl just means `usemtl` below
+                    // falls back to `fallback` per name.
+                }
+            }
+            Some("usemtl") => {
+                if let Some(name) = parts.next() {
+                    current_material = materials.get(name).copied().unwrap_or(fallback);
+                    if let Some(last) = group_materials.last_mut() {
+                        *last = current_material;
+                    }
+                }
+            }
+            Some("o") | Some("g") => {
+                let name = parts.next().unwrap_or("group").to_string();
+                groups.push((name, Vec::new()));
+                group_materials.push(current_material);
+            }
             Some("f") => {
-                let idx: Vec<usize> = parts
-                    .filter_map(|s| s.split('/').next().unwrap_or("").parse::<usize>().ok())
-                    .collect();
-                if idx.len() >= 3 {
-                    let first = idx[0] - 1;
-                    let mut prev = idx[1] - 1;
-                    for &i in &idx[2..] {
-                        let v0 = verts[first];
-                        let v1 = verts[prev];
-                        let v2 = verts[i - 1];
-                        tris.push([v0, v1, v2]);
-                        prev = i - 1;
+                let verts: Vec<(usize, Option<usize>, Option<usize>)> =
+                    parts.filter_map(parse_face_vertex).collect();
+                if verts.len() >= 3 {
+                    let (p0i, t0i, n0i) = verts[0];
+                    let p0 = positions[p0i];
+                    for w in 1..verts.len() - 1 {
+                        let (p1i, t1i, n1i) = verts[w];
+                        let (p2i, t2i, n2i) = verts[w + 1];
+                        let p1 = positions[p1i];
+                        let p2 = positions[p2i];
+                        let normal = (p1 - p0).cross(p2 - p0).normalize();
+                        let vertex_normals = match (n0i, n1i, n2i) {
+                            (Some(a), Some(b), Some(c)) => Some([normals[a], normals[b], normals[c]]),
+                            _ => None,
+                        };
+                        let vertex_uvs = match (t0i, t1i, t2i) {
+                            (Some(a), Some(b), Some(c)) => Some([texcoords[a], texcoords[b], texcoords[c]]),
+                            _ => None,
+                        };
+                        groups.last_mut().unwrap().1.push(Triangle { v0: p0, v1: p1, v2: p2, normal, vertex_normals, vertex_uvs });
                     }
                 }
             }
@@ -93,5 +309,11 @@ pub fn load_obj(path: &str) -> Vec<[Vec3; 3]> {
         }
     }
 
-    tris
+    let meshes = groups
+        .into_iter()
+        .zip(group_materials)
+        .filter(|((_, tris), _)| !tris.is_empty())
+        .map(|((name, tris), material)| Mesh::build(name, tris, material, true))
+        .collect();
+    Ok(meshes)
 }
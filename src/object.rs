@@ -1,30 +1,48 @@
 use crate::{algebra::Vec3, material::Material};
+use crate::bvh::Aabb;
 
 #[derive(Clone)]
 pub enum Object {
     Sphere(crate::sphere::Sphere),
-    Plane(crate::plane::Plane),
+    Rect(crate::plane::Rect),
+    InfinitePlane(crate::plane::InfinitePlane),
     Mesh(crate::mesh::Mesh),
 }
 
 impl Object {
+    /// Nearest intersection with `t` in `(t_min, t_max)`.
     pub fn hit(
         &self,
         ro: crate::algebra::Vec3,
         rd: crate::algebra::Vec3,
+        time: f32,
+        t_min: f32,
+        t_max: f32,
     ) -> Option<(f32, crate::algebra::Vec3, crate::material::Material)> {
         match self {
-            Self::Sphere(s) => s.hit(ro, rd),
-            Self::Plane(p) => p.hit(ro, rd),
-            Self::Mesh(m) => m.hit(ro, rd),
+            Self::Sphere(s) => s.hit(ro, rd, time, t_min, t_max),
+            Self::Rect(p) => p.hit(ro, rd, t_min, t_max),
+            Self::InfinitePlane(p) => p.hit(ro, rd, t_min, t_max),
+            Self::Mesh(m) => m.hit(ro, rd, t_min, t_max),
         }
     }
 
     pub fn is_in_focus(&self) -> bool {
         match self {
             Self::Sphere(s) => s.in_focus,
-            Self::Plane(p) => p.in_focus,
+            Self::Rect(p) => p.in_focus,
+            Self::InfinitePlane(p) => p.in_focus,
             Self::Mesh(m) => m.in_focus,
         }
     }
+
+    /// Conservative bounding box, used to build the scene-level BVH.
+    pub fn aabb(&self) -> Aabb {
+        match self {
+            Self::Sphere(s) => s.aabb(),
+            Self::Rect(p) => p.aabb(),
+            Self::InfinitePlane(p) => p.aabb(),
+            Self::Mesh(m) => m.aabb(),
+        }
+    }
 }
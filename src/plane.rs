@@ -1,55 +1,145 @@
 use crate::algebra::Vec3;
 use crate::material::Material;
+use crate::bvh::Aabb;
+
+/// Ray/plane intersection against the infinite supporting surface through
+/// `point` with `normal`: rejects rays parallel to the plane and hits
+/// outside `(t_min, t_max)`. Shared by [`Rect`] and [`InfinitePlane`] so both
+/// guard the same way before doing their own (or no) bounds check.
+pub(crate) fn intersect_plane(ro: Vec3, rd: Vec3, point: Vec3, normal: Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+    let denom = normal.dot(rd);
+    if denom.abs() < 1e-6 { return None; } // Ray is parallel
+
+    let t = point.sub(ro).dot(normal) / denom;
+    if !t.is_finite() || t <= t_min || t >= t_max { return None; }
+    Some(t)
+}
+
+/// Two-sided shading normal: flips the supporting surface's `normal` to
+/// face the incoming ray.
+fn facing_normal(normal: Vec3, rd: Vec3) -> Vec3 {
+    if normal.dot(rd) < 0.0 { normal } else { normal.neg() }
+}
 
-/// Finite rectangle defined by center-point, normal and half-sizes.
 /// Finite rectangle defined by center-point, and two edge vectors u and v.
 #[derive(Clone)]
-pub struct Plane {
+pub struct Rect {
     pub name    : String,
     pub point   : Vec3,
     pub u       : Vec3, // Vector from center to one edge (encodes direction and half-width)
     pub v       : Vec3, // Vector from center to another edge (encodes direction and half-height)
     pub normal  : Vec3, // Pre-calculated normal (u.cross(v))
     pub material: Material,
+    pub in_focus: bool,
 }
-impl Plane {
-    /// Returns (t, hit_normal, material) or `None` if the ray misses.
+impl Rect {
+    /// Returns (t, hit_normal, material) for the nearest intersection with
+    /// `t` in `(t_min, t_max)`, or `None` if the ray misses.
     pub(crate) fn hit(
         &self,
         ro: Vec3,
         rd: Vec3,
+        t_min: f32,
+        t_max: f32,
     ) -> Option<(f32, Vec3, Material)> {
-        // Intersection with the plane's infinite supporting surface
-        let denom = self.normal.dot(rd);
-        if denom.abs() < 1e-6 { return None; } // Ray is parallel
-
-        let t = self.point.sub(ro).dot(self.normal) / denom;
-        if !t.is_finite() || t <= 1e-4 { return None; }
+        let t = intersect_plane(ro, rd, self.point, self.normal, t_min, t_max)?;
+        let hit = ro.add(rd.scale(t));
+        if !self.contains_point(hit) { return None; }
+        let material = self.material.with_albedo_at(Some(self.uv_at(hit)));
+        Some((t, facing_normal(self.normal, rd), material))
+    }
 
-        // Determine correct normal for two-sided lighting
-        let hit_normal = if denom < 0.0 { self.normal } else { self.normal.neg() };
+    /// UV of a point already known to lie on the rectangle: its projection
+    /// onto the `u`/`v` basis (the same `a`/`b` [`contains_point`] checks
+    /// are in `[-1, 1]`), remapped to `[0, 1]`.
+    fn uv_at(&self, p: Vec3) -> (f32, f32) {
+        let d = p.sub(self.point);
+        let a = d.dot(self.u) / self.u.dot(self.u);
+        let b = d.dot(self.v) / self.v.dot(self.v);
+        (0.5 * (a + 1.0), 0.5 * (b + 1.0))
+    }
 
-        let hit = ro.add(rd.scale(t));
-        let d = hit.sub(self.point); // Vector from plane center to hit point
+    /// True if `p` — assumed to already lie on the rectangle's supporting
+    /// plane — projects onto the `u`/`v` basis within `|a| <= 1, |b| <= 1`,
+    /// i.e. inside the rectangle. Lets scene code query membership without
+    /// casting a ray.
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        let d = p.sub(self.point); // Vector from plane center to hit point
 
-        // --- NEW, ROBUST BOUNDS CHECK ---
         // Project the vector 'd' onto the plane's basis vectors 'u' and 'v'.
-        // If the hit point is inside the rectangle, its coordinates (a, b) in the
+        // If the point is inside the rectangle, its coordinates (a, b) in the
         // u,v basis must satisfy |a| <= 1 and |b| <= 1.
         // a = (d . u) / (u . u)
         // b = (d . v) / (v . v)
-
         let du = d.dot(self.u);
         let u2 = self.u.dot(self.u);
-
-        if du.abs() > u2 { return None; }
+        if du.abs() > u2 { return false; }
 
         let dv = d.dot(self.v);
         let v2 = self.v.dot(self.v);
+        dv.abs() <= v2
+    }
 
-        if dv.abs() > v2 { return None; }
+    /// Bounding box of the finite rectangle, padded slightly along the
+    /// normal so axis-aligned planes don't produce a degenerate slab.
+    pub fn aabb(&self) -> Aabb {
+        let eps = Vec3(1e-4, 1e-4, 1e-4);
+        let c0 = self.point.add(self.u).add(self.v);
+        let c1 = self.point.add(self.u).sub(self.v);
+        let c2 = self.point.sub(self.u).add(self.v);
+        let c3 = self.point.sub(self.u).sub(self.v);
+        Aabb::from_points(c0, c1).grow(c2).grow(c3).union(Aabb::from_points(
+            self.point.sub(eps),
+            self.point.add(eps),
+        ))
+    }
+}
 
-        // We have a hit
-        Some((t, hit_normal, self.material))
+/// An unbounded ground-plane-style primitive: the same supporting-surface
+/// math as [`Rect`], but `hit` has no bounds check at all, so the whole
+/// infinite surface is solid.
+#[derive(Clone)]
+pub struct InfinitePlane {
+    pub name    : String,
+    pub point   : Vec3,
+    pub normal  : Vec3,
+    pub material: Material,
+    pub in_focus: bool,
+}
+impl InfinitePlane {
+    pub(crate) fn hit(
+        &self,
+        ro: Vec3,
+        rd: Vec3,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<(f32, Vec3, Material)> {
+        let t = intersect_plane(ro, rd, self.point, self.normal, t_min, t_max)?;
+        Some((t, facing_normal(self.normal, rd), self.material))
     }
-}
\ No newline at end of file
+
+    /// Half-extent used to stand in for "infinite" in the tangent
+    /// directions of [`aabb`](Self::aabb): large enough to swallow any
+    /// reasonable scene, but finite so the BVH's ray-slab test never
+    /// multiplies a zero ray-direction component by an actual infinity.
+    const HUGE_EXTENT: f32 = 1.0e6;
+
+    /// Bounding box: tight along the normal, huge (but finite) along the
+    /// plane's two tangent directions — same shape as [`Rect::aabb`], with
+    /// synthetic `u`/`v` standing in for the missing bounds.
+    pub fn aabb(&self) -> Aabb {
+        let tangent0 = self.normal.any_orthonormal().normalize();
+        let tangent1 = self.normal.cross(tangent0);
+        let u = tangent0.scale(Self::HUGE_EXTENT);
+        let v = tangent1.scale(Self::HUGE_EXTENT);
+        let eps = self.normal.scale(1e-4);
+        let c0 = self.point.add(u).add(v);
+        let c1 = self.point.add(u).sub(v);
+        let c2 = self.point.sub(u).add(v);
+        let c3 = self.point.sub(u).sub(v);
+        Aabb::from_points(c0, c1).grow(c2).grow(c3).union(Aabb::from_points(
+            self.point.sub(eps),
+            self.point.add(eps),
+        ))
+    }
+}
@@ -1,10 +1,12 @@
 use crate::{
     algebra::{sample_disk, Vec3},
-    ggx::{d_term, g_term, fresnel_schlick, reflect, sample_ggx_h},
-    material::Material,
+    bvh::BvhNode,
+    ggx::{d_term, fresnel_schlick, g1_term, g_term, reflect, sample_ggx_vndf},
+    material::{Material, MaterialMode},
     light::Light,
     object::Object,
-    tonemap,
+    sky::Sky,
+    tonemap::ToneMapping,
 };
 use image::Rgb;
 use rand::Rng;
@@ -15,12 +17,136 @@ use std::ops::Mul;
 const MAX_DEPTH: u32 = 5;
 const RUSSIAN_ROULETTE_DEPTH: u32 = 2;
 
+/// Minimum ray-parameter accepted by any intersection test, keeping a ray
+/// from immediately re-hitting the surface it just left.
+const T_MIN: f32 = 1e-4;
+
+/// Whether anything in `objs` occludes the segment `ro + t*rd` for `t` in
+/// `(T_MIN, t_max)`, using the scene BVH to prune the search instead of
+/// scanning every object.
+fn occluded(bvh: &BvhNode, objs: &[Object], ro: Vec3, rd: Vec3, t_max: f32, time: f32) -> bool {
+    let inv_rd = Vec3(1.0 / rd.0, 1.0 / rd.1, 1.0 / rd.2);
+    let mut closest = t_max;
+    bvh.traverse(ro, rd, inv_rd, T_MIN, &mut closest, &mut |start, count, local_max| {
+        objs[start..start + count]
+            .iter()
+            .filter_map(|o| o.hit(ro, rd, time, T_MIN, local_max))
+            .map(|(t, _, _)| t)
+            .fold(None, |acc: Option<f32>, t| Some(acc.map_or(t, |b| b.min(t))))
+    });
+    closest < t_max
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Uniform offset inside a sphere of `radius`, used to jitter point/spot
+/// light positions for soft shadows. Returns zero for a radius-less light.
+fn sample_sphere_offset(radius: f32, rng: &mut impl Rng) -> Vec3 {
+    if radius <= 0.0 { return Vec3(0.0, 0.0, 0.0); }
+    loop {
+        let p = Vec3(rng.gen::<f32>() * 2.0 - 1.0, rng.gen::<f32>() * 2.0 - 1.0, rng.gen::<f32>() * 2.0 - 1.0);
+        if p.dot(p) <= 1.0 {
+            return p.scale(radius);
+        }
+    }
+}
+
+/// Fades a light's contribution smoothly to zero past `influence`, bounding
+/// how far shadow rays need to reach for that light.
+fn influence_falloff(dist: f32, influence: Option<f32>) -> f32 {
+    match influence {
+        Some(inf) if dist >= inf => 0.0,
+        Some(inf) => 1.0 - smoothstep(0.0, inf, dist),
+        None => 1.0,
+    }
+}
 
+/// Draws one sample of a light as seen from `hit`: the direction toward it,
+/// the shadow-ray `t_max` to test occlusion up to, and the unshadowed
+/// incident radiance (not yet multiplied by `n_dot_l` or the BRDF).
+fn sample_light(light: &Light, hit: Vec3, rng: &mut impl Rng) -> Option<(Vec3, f32, Vec3)> {
+    match *light {
+        Light::Area { pos, u, v, intensity } => {
+            let lp = pos + u * (rng.gen::<f32>() - 0.5) + v * (rng.gen::<f32>() - 0.5);
+            let lvec = lp - hit;
+            let dist2 = lvec.dot(lvec);
+            let l = lvec.normalize();
+            let light_area = u.cross(v).norm();
+            let light_normal = u.cross(v).normalize();
+            let cos_theta_light = (-l).dot(light_normal).max(0.0);
+            if cos_theta_light <= 0.0 { return None; }
+            let falloff = cos_theta_light / dist2;
+            Some((l, (dist2 * 0.999).sqrt(), intensity.scale(light_area * falloff)))
+        }
+        Light::Point { pos, intensity, radius, influence } => {
+            let lp = pos + sample_sphere_offset(radius, rng);
+            let lvec = lp - hit;
+            let dist2 = lvec.dot(lvec).max(1e-8);
+            let dist = dist2.sqrt();
+            let l = lvec.scale(1.0 / dist);
+            let falloff = influence_falloff(dist, influence) / dist2;
+            if falloff <= 0.0 { return None; }
+            Some((l, dist * 0.999, intensity.scale(falloff)))
+        }
+        Light::Spot { pos, dir, intensity, cos_size, cos_blend, influence } => {
+            let lvec = pos - hit;
+            let dist2 = lvec.dot(lvec).max(1e-8);
+            let dist = dist2.sqrt();
+            let l = lvec.scale(1.0 / dist);
+            let cos_angle = (-l).dot(dir);
+            if cos_angle < cos_size { return None; }
+            let cone = smoothstep(cos_size, cos_blend, cos_angle);
+            let falloff = cone * influence_falloff(dist, influence) / dist2;
+            if falloff <= 0.0 { return None; }
+            Some((l, dist * 0.999, intensity.scale(falloff)))
+        }
+        Light::Directional { dir, intensity } => {
+            Some((-dir, f32::INFINITY, intensity))
+        }
+    }
+}
+
+/// Closed-form irradiance from a rectangular area light over the
+/// cosine-weighted hemisphere at `hit`, with no stochastic sampling — the
+/// exact (identity-matrix) case of EEVEE's linearly-transformed-cosine
+/// area-light technique. Zero variance, but ignores occlusion.
+fn analytic_rect_irradiance(pos: Vec3, u: Vec3, v: Vec3, hit: Vec3, n: Vec3) -> f32 {
+    // Wound so the edge sum points toward the light's emitting `u x v` side,
+    // making the result positive for a point the light faces.
+    let corners = [
+        pos - u.scale(0.5) - v.scale(0.5),
+        pos - u.scale(0.5) + v.scale(0.5),
+        pos + u.scale(0.5) + v.scale(0.5),
+        pos + u.scale(0.5) - v.scale(0.5),
+    ];
+    let w: [Vec3; 4] = corners.map(|c| (c - hit).normalize());
+
+    let mut vec_sum = Vec3(0.0, 0.0, 0.0);
+    for i in 0..4 {
+        let w_i = w[i];
+        let w_j = w[(i + 1) % 4];
+        let theta = w_i.dot(w_j).clamp(-1.0, 1.0).acos();
+        let cross = w_i.cross(w_j);
+        let cross_norm = cross.norm();
+        if cross_norm > 1e-8 {
+            vec_sum = vec_sum + cross.scale(theta / cross_norm);
+        }
+    }
+    vec_sum.dot(n).max(0.0) / (2.0 * PI)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn direct_light_sample(
     hit: Vec3, n: Vec3, v: Vec3,
     mat: Material,
     objs: &[Object],
+    bvh: &BvhNode,
     lights: &[Light],
+    sky: &Sky,
+    time: f32,
     rng: &mut impl Rng,
 ) -> Vec3 {
     let mut total_direct_light = Vec3(0.0, 0.0, 0.0);
@@ -29,32 +155,23 @@ fn direct_light_sample(
     const SHADOW_SAMPLES: u32 = 4; // Increase this for smoother shadows at the cost of performance. 4 is a good balance.
 
     for light in lights {
-        let mut light_contrib = Vec3(0.0, 0.0, 0.0);
-
-        // --- NEW: Loop to cast multiple shadow rays ---
-        for _ in 0..SHADOW_SAMPLES {
-            // Sample a point on the light source
-            let lp = light.pos
-                + light.u * (rng.gen::<f32>() - 0.5)
-                + light.v * (rng.gen::<f32>() - 0.5);
-            let lvec = lp - hit;
-            let dist2 = lvec.dot(lvec);
-            let l = lvec.normalize();
-
-            // Check for visibility (shadow ray)
-            let shadow_ro = hit + l * 1e-4;
-            if objs.iter().any(|o| o.hit(shadow_ro, l).map_or(false, |(t, _, _)| t * t < dist2 * 0.999))
-            { continue; }
-
-            let n_dot_l = n.dot(l).max(0.0);
-            if n_dot_l > 0.0 {
-                let light_area = light.u.cross(light.v).norm();
-                let light_normal = light.u.cross(light.v).normalize();
-                let cos_theta_light = (-l).dot(light_normal).max(0.0);
-
-                if cos_theta_light > 0.0 {
-                    let falloff = cos_theta_light / dist2;
-
+        if let Light::Area { pos, u, v: light_v, intensity } = *light {
+            // Diffuse: analytic, zero-variance irradiance, scaled by a
+            // stochastic visibility estimate so it still darkens in shadow.
+            let diffuse_albedo = mat.color * (1.0 - mat.metallic) * (1.0 / PI);
+            let irradiance = analytic_rect_irradiance(pos, u, light_v, hit, n);
+
+            // Specular: still needs the light's solid angle sampled, so keep
+            // the stochastic shadow-ray loop for that term only.
+            let mut visible_samples = 0u32;
+            let mut spec_accum = Vec3(0.0, 0.0, 0.0);
+            for _ in 0..SHADOW_SAMPLES {
+                let Some((l, shadow_t_max, incident)) = sample_light(light, hit, rng) else { continue; };
+                if occluded(bvh, objs, hit, l, shadow_t_max, time) { continue; }
+                visible_samples += 1;
+
+                let n_dot_l = n.dot(l).max(0.0);
+                if n_dot_l > 0.0 {
                     let h = (v + l).normalize();
                     let n_dot_v = n.dot(v).max(1e-4);
                     let n_dot_h = n.dot(h).max(0.0);
@@ -69,61 +186,28 @@ fn direct_light_sample(
                     let spec_denominator = 4.0 * n_dot_v * n_dot_l;
                     let specular_brdf = spec_numerator * (1.0 / (spec_denominator + 1e-6));
 
-                    let diffuse_color = mat.color * (1.0 - mat.metallic);
-                    let k_d = Vec3(1.0, 1.0, 1.0) - f;
-                    let diffuse_brdf = diffuse_color.mul(k_d) * (1.0 / PI);
-
-                    let radiance = (diffuse_brdf + specular_brdf) * n_dot_l;
-                    light_contrib = light_contrib + radiance.mul(light.intensity).scale(light_area * falloff);
+                    spec_accum = spec_accum + (specular_brdf * n_dot_l).mul(incident);
                 }
             }
+            let visibility = visible_samples as f32 / SHADOW_SAMPLES as f32;
+            let diffuse = diffuse_albedo.scale(irradiance * visibility).mul(intensity);
+            total_direct_light = total_direct_light + diffuse + spec_accum.scale(1.0 / SHADOW_SAMPLES as f32);
+            continue;
         }
-        // Average the contribution from all shadow samples
-        total_direct_light = total_direct_light + light_contrib.scale(1.0 / SHADOW_SAMPLES as f32);
-    }
-    total_direct_light
-}
-
-
-// ... lighting, render_image_name, pixel_color, autofocus functions remain the same as the previous answer ...
 
-fn lighting(
-    hit: Vec3, n: Vec3, v: Vec3,
-    mat: Material,
-    objects: &[Object],
-    lights : &[Light],
-    rng: &mut impl Rng,
-) -> Vec3 {
-    let mut total_direct_light = Vec3(0.0, 0.0, 0.0);
-
-    for light in lights {
         let mut light_contrib = Vec3(0.0, 0.0, 0.0);
-        let samples = 1; // Direct light is expensive, we can rely on pixel samples
 
-        for _ in 0..samples {
-            let lp = light.pos
-                + light.u * (rng.gen::<f32>() - 0.5)
-                + light.v * (rng.gen::<f32>() - 0.5);
-            let lvec = lp - hit;
-            let dist2 = lvec.dot(lvec);
-            let l = lvec.normalize();
+        // --- NEW: Loop to cast multiple shadow rays ---
+        for _ in 0..SHADOW_SAMPLES {
+            let Some((l, shadow_t_max, incident)) = sample_light(light, hit, rng) else { continue; };
 
-            let shadow_ro = hit + n * 1e-4;
-            if objects.iter().any(|o|
-                o.hit(shadow_ro, l)
-                    .map_or(false, |(t, _, _)| t * t < dist2))
+            // Check for visibility (shadow ray)
+            let shadow_ro = hit;
+            if occluded(bvh, objs, shadow_ro, l, shadow_t_max, time)
             { continue; }
 
             let n_dot_l = n.dot(l).max(0.0);
             if n_dot_l > 0.0 {
-                // --- NEW: Area Light Attenuation ---
-                // For area lights, we must account for the solid angle they occupy.
-                // This term scales the light based on its area and distance.
-                let light_area = light.u.cross(light.v).norm();
-                let light_normal = light.u.cross(light.v).normalize();
-                let cos_theta_light = (-l).dot(light_normal).max(0.0);
-                let falloff = cos_theta_light / (dist2 + 1e-4); // +1 for no light at source
-
                 let h = (v + l).normalize();
                 let n_dot_v = n.dot(v).max(1e-4);
                 let n_dot_h = n.dot(h).max(0.0);
@@ -142,13 +226,49 @@ fn lighting(
                 let k_d = Vec3(1.0, 1.0, 1.0) - f;
                 let diffuse_brdf = diffuse_color.mul(k_d) * (1.0 / PI);
 
-                // Combine and scale by light properties
                 let radiance = (diffuse_brdf + specular_brdf) * n_dot_l;
-                light_contrib = light_contrib + radiance.mul(light.intensity).scale(light_area * falloff);
+                light_contrib = light_contrib + radiance.mul(incident);
             }
         }
-        total_direct_light = total_direct_light + light_contrib * (1.0 / samples as f32);
+        // Average the contribution from all shadow samples
+        total_direct_light = total_direct_light + light_contrib.scale(1.0 / SHADOW_SAMPLES as f32);
+    }
+
+    // Environment light: next-event estimation against the sky, drawn from
+    // its own importance distribution (uniform for the gradient, luminance
+    // CDF for an HDR map) and weighted by `1/pdf` like any other
+    // importance-sampled estimator.
+    let mut sky_contrib = Vec3(0.0, 0.0, 0.0);
+    for _ in 0..SHADOW_SAMPLES {
+        let (l, radiance, pdf) = sky.sample(rng);
+        if pdf <= 0.0 || occluded(bvh, objs, hit, l, f32::INFINITY, time) { continue; }
+
+        let n_dot_l = n.dot(l).max(0.0);
+        if n_dot_l > 0.0 {
+            let h = (v + l).normalize();
+            let n_dot_v = n.dot(v).max(1e-4);
+            let n_dot_h = n.dot(h).max(0.0);
+            let v_dot_h = v.dot(h).max(0.0);
+
+            let f0 = Vec3(0.04, 0.04, 0.04) * (1.0 - mat.metallic) + mat.color * mat.metallic;
+            let f = fresnel_schlick(v_dot_h, f0);
+            let d = d_term(n_dot_h, mat.roughness);
+            let g = g_term(n_dot_v, n_dot_l, mat.roughness);
+
+            let spec_numerator = f * d * g;
+            let spec_denominator = 4.0 * n_dot_v * n_dot_l;
+            let specular_brdf = spec_numerator * (1.0 / (spec_denominator + 1e-6));
+
+            let diffuse_color = mat.color * (1.0 - mat.metallic);
+            let k_d = Vec3(1.0, 1.0, 1.0) - f;
+            let diffuse_brdf = diffuse_color.mul(k_d) * (1.0 / PI);
+
+            let brdf_radiance = (diffuse_brdf + specular_brdf) * n_dot_l;
+            sky_contrib = sky_contrib + brdf_radiance.mul(radiance).scale(1.0 / pdf);
+        }
     }
+    total_direct_light = total_direct_light + sky_contrib.scale(1.0 / SHADOW_SAMPLES as f32);
+
     total_direct_light
 }
 
@@ -160,35 +280,66 @@ pub fn render_image_name(w:u32,h:u32,s:u32,ap:f32,f:f32)->String{
     format!("renders/render_{w}x{h}_s{s}_ap{ap:.2}_f{f:.1}_{suf}.jpg")
 }
 
+/// Traces a single camera-ray sample through pixel `(x, y)`, offset within
+/// the pixel by `(jx, jy)` (each in `[0, 1)`), returning raw (linear,
+/// untonemapped) radiance. Factored out of [`pixel_color`] so a tiled,
+/// pass-based coordinator (see [`crate::tile`]) can accumulate one sample
+/// at a time instead of committing to a fixed `samples` count up front, and
+/// so the caller can reuse the same jittered position to splat the sample
+/// onto the [`crate::film::Film`] for reconstruction filtering.
+#[allow(clippy::too_many_arguments)]
+pub fn sample_radiance(
+    x:u32,y:u32,w:u32,h:u32,jx:f32,jy:f32,aspect:f32,scale:f32,
+    cam:Vec3,right:Vec3,up:Vec3,forward:Vec3,focus:f32,aperture:f32,
+    shutter0:f32,shutter1:f32,
+    objs:&[Object],bvh:&BvhNode,lights:&[Light],sky:&Sky,rng:&mut impl Rng)->Vec3
+{
+    let u  = ((x as f32 + jx)/w as f32 -0.5)*2.0*aspect*scale;
+    let v  = -((y as f32 + jy)/h as f32 -0.5)*2.0*scale;
+    let rd0 = (right*u + up*v + forward).normalize();
+    let (dx,dy)  = sample_disk(aperture);
+    let focal_pt = cam + rd0*focus;
+    let origin   = cam + right*dx + up*dy;
+    let rd       = (focal_pt - origin).normalize();
+    // Draw a shutter time per sample so moving spheres blur over the
+    // exposure interval instead of rendering a frozen instant.
+    let time = shutter0 + rng.gen::<f32>() * (shutter1 - shutter0);
+
+    // Initial call to trace starts with no medium; a camera ray counts
+    // as a specular bounce so it can see emissive surfaces directly.
+    trace(origin, rd, objs, bvh, lights, sky, 0, rng, None, true, time)
+}
+
+/// Tonemaps and gamma-encodes a mean linear radiance into a displayable
+/// 8-bit-per-channel color using `tm`'s operator, exposure and gamma.
+pub fn tonemap_to_rgb8(mean: Vec3, tm: &ToneMapping) -> [u8; 3] {
+    let display = tm.apply(mean);
+    [
+        (display.0*255.0).min(255.0) as u8,
+        (display.1*255.0).min(255.0) as u8,
+        (display.2*255.0).min(255.0) as u8
+    ]
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn pixel_color(
     x:u32,y:u32,w:u32,h:u32,samples:u32,aspect:f32,scale:f32,
     cam:Vec3,right:Vec3,up:Vec3,forward:Vec3,focus:f32,aperture:f32,
-    objs:&[Object],lights:&[Light],rng:&mut impl Rng)->[u8;3]
+    shutter0:f32,shutter1:f32,
+    objs:&[Object],bvh:&BvhNode,lights:&[Light],sky:&Sky,tonemapping:&ToneMapping,rng:&mut impl Rng)->[u8;3]
 {
     let mut col = Vec3(0.0,0.0,0.0);
     for _ in 0..samples {
         let jx = rng.gen::<f32>();
         let jy = rng.gen::<f32>();
-        let u  = ((x as f32 + jx)/w as f32 -0.5)*2.0*aspect*scale;
-        let v  = -((y as f32 + jy)/h as f32 -0.5)*2.0*scale;
-        let rd0 = (right*u + up*v + forward).normalize();
-        let (dx,dy)  = sample_disk(aperture);
-        let focal_pt = cam + rd0*focus;
-        let origin   = cam + right*dx + up*dy;
-        let rd       = (focal_pt - origin).normalize();
-
-        // Initial call to trace starts with no medium.
-        col = col + trace(origin, rd, objs, lights, 0, rng, None);
+        col = col + sample_radiance(
+            x, y, w, h, jx, jy, aspect, scale,
+            cam, right, up, forward, focus, aperture,
+            shutter0, shutter1,
+            objs, bvh, lights, sky, rng);
     }
 
-    let avg_col = col * (1.0/samples as f32);
-    let tonemapped_col = tonemap::aces_film(avg_col);
-
-    [
-        (tonemapped_col.0.powf(1.0/2.2)*255.0).min(255.0) as u8,
-        (tonemapped_col.1.powf(1.0/2.2)*255.0).min(255.0) as u8,
-        (tonemapped_col.2.powf(1.0/2.2)*255.0).min(255.0) as u8
-    ]
+    tonemap_to_rgb8(col * (1.0/samples as f32), tonemapping)
 }
 
 fn sample_phase_function(g: f32, rng: &mut impl Rng) -> f32 {
@@ -203,7 +354,7 @@ fn sample_phase_function(g: f32, rng: &mut impl Rng) -> f32 {
 
 pub fn autofocus(
     cam: Vec3, right: Vec3, up: Vec3, forward: Vec3,
-    aspect: f32, scale: f32, w: u32, h: u32, objs: &[Object]
+    aspect: f32, scale: f32, w: u32, h: u32, objs: &[Object], bvh: &BvhNode
 ) -> f32 {
     let mut dists = Vec::new();
     for i in 0..5 {
@@ -216,7 +367,7 @@ pub fn autofocus(
 
             let dir = (right.scale(u) + up.scale(v) + forward).normalize();
 
-            if let Some((t, _n, _)) = intersect_closest(cam, dir, objs) {
+            if let Some((t, _n, _)) = intersect_closest(cam, dir, objs, bvh, 0.0) {
                 dists.push(t);
             }
         }
@@ -226,19 +377,24 @@ pub fn autofocus(
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn trace(
     ro: Vec3,
     rd: Vec3,
     objs: &[Object],
+    bvh: &BvhNode,
     lights: &[Light],
+    sky: &Sky,
     depth: u32,
     rng: &mut impl Rng,
     mut current_media: Option<Material>,
+    specular_bounce: bool,
+    time: f32,
 ) -> Vec3 {
     if depth >= MAX_DEPTH { return Vec3(0.0, 0.0, 0.0); }
 
     // --- 1. Find the next potential surface interaction ---
-    let surface_hit = intersect_closest(ro, rd, objs);
+    let surface_hit = intersect_closest(ro, rd, objs, bvh, time);
     let t_surface = surface_hit.as_ref().map_or(f32::INFINITY, |(t, _, _)| *t);
 
     // --- 2. Ray March through the current medium (if any) ---
@@ -262,7 +418,7 @@ pub fn trace(
         let hit_point = ro + rd * t_media;
 
         // Add direct lighting at the scatter point (for god rays)
-        let direct_light = direct_light_sample(hit_point, Vec3(0.0,1.0,0.0), -rd, current_media.unwrap(), objs, lights, rng);
+        let direct_light = direct_light_sample(hit_point, Vec3(0.0,1.0,0.0), -rd, current_media.unwrap(), objs, bvh, lights, sky, time, rng);
 
         // Scatter the ray using the phase function
         let w = rd;
@@ -273,14 +429,23 @@ pub fn trace(
         let phi = 2.0 * PI * rng.gen::<f32>();
         let next_dir = (u * phi.cos() * sin_theta + v_cross * phi.sin() * sin_theta + w * cos_theta).normalize();
 
-        // Recurse from the scatter point, staying in the same medium
-        return (direct_light + trace(hit_point, next_dir, objs, lights, depth + 1, rng, current_media)).mul(absorption);
+        // Recurse from the scatter point, staying in the same medium; a
+        // medium scatter event is not a specular bounce.
+        return (direct_light + trace(hit_point, next_dir, objs, bvh, lights, sky, depth + 1, rng, current_media, false, time)).mul(absorption);
     }
 
     // B. Surface hit event happens first (or no medium)
     let (t, n, mut mat) = match surface_hit {
         Some(v) => v,
-        None => return Vec3(0.0, 0.0, 0.0).mul(absorption) // Hit sky, attenuated by any medium we passed through
+        None => {
+            // Hit the sky, attenuated by any medium we passed through. Like
+            // emission, only count it directly on a camera/specular ray;
+            // diffuse/glossy bounces already get the sky's contribution via
+            // next-event estimation, so adding it here too would
+            // double-count it.
+            let sky_radiance = if specular_bounce { sky.radiance(rd) } else { Vec3(0.0, 0.0, 0.0) };
+            return sky_radiance.mul(absorption);
+        }
     };
 
     let hit = ro + rd * t;
@@ -289,6 +454,14 @@ pub fn trace(
     mat.metallic = mat.metallic.clamp(0.0, 1.0);
     mat.roughness = mat.roughness.clamp(0.01, 1.0);
 
+    // Emissive Rects are folded into `lights` at scene::load and so are
+    // already next-event-estimated every bounce, exactly like the sky and
+    // the explicit `lights` array; count their emission here only on a
+    // camera/specular ray, or a diffuse/glossy bounce would double-count it.
+    // Emissive Sphere/Mesh/InfinitePlane surfaces have no such NEE path yet,
+    // so they're always added directly instead (noisier, but not biased).
+    let emitted = if specular_bounce || !mat.light_sampled { mat.emission } else { Vec3(0.0, 0.0, 0.0) };
+
     // Determine the medium for the *next* ray bounce
     let next_media = if mat.volume_density > 0.0 {
         if v.dot(n) > 0.0 { Some(mat) } else { None } // Entering vs. Exiting
@@ -296,7 +469,7 @@ pub fn trace(
         current_media
     };
 
-    if mat.ior > 1.0 && mat.metallic < 0.1 { // Glass Surface
+    if mat.mode == MaterialMode::Dielectric { // Glass Surface
         // Standard glass logic, but the recursive call passes the `next_media`
         let cosi = v.dot(n).clamp(-1.0, 1.0);
         let (etai, etat) = if cosi > 0.0 { (1.0, mat.ior) } else { (mat.ior, 1.0) };
@@ -308,32 +481,64 @@ pub fn trace(
         else if let Some(refract_dir) = refract(-v, hit_normal, etai / etat) { refract_dir }
         else { reflect(-v, hit_normal) };
 
-        return trace(hit + next_dir * 1e-4, next_dir, objs, lights, depth + 1, rng, next_media).mul(absorption);
+        return (emitted + trace(hit, next_dir, objs, bvh, lights, sky, depth + 1, rng, next_media, true, time)).mul(absorption);
     }
 
     // Opaque Surface
-    let direct_light = direct_light_sample(hit, n, v, mat, objs, lights, rng);
+    let direct_light = direct_light_sample(hit, n, v, mat, objs, bvh, lights, sky, time, rng);
     let mut indirect_light = Vec3(0.0, 0.0, 0.0);
 
     // Russian Roulette, etc.
     let p = mat.color.0.max(mat.color.1).max(mat.color.2);
     if depth < RUSSIAN_ROULETTE_DEPTH || rng.gen::<f32>() < p {
-        let (next_dir, brdf) = if rng.gen::<f32>() < (1.0 - mat.metallic) { // Diffuse
-            let w = n;
-            let u = w.any_orthonormal().normalize();
-            let v_cross = w.cross(u);
-            let phi = 2.0 * PI * rng.gen::<f32>();
-            let r2: f32 = rng.gen();
-            ( (u * phi.cos() * r2.sqrt() + v_cross * phi.sin() * r2.sqrt() + w * (1.0 - r2).sqrt()).normalize(),
-              mat.color * (1.0 / PI) )
-        } else { // Specular
-            let h = sample_ggx_h(n, mat.roughness, rng);
-            ( reflect(-v, h),
-              Vec3(1.0,1.0,1.0) ) // Specular BRDF handled by fresnel in throughput
+        // The material mode picks the indirect-bounce lobe explicitly,
+        // rather than inferring mirror-vs-glossy from metallic/roughness.
+        let is_specular = !matches!(mat.mode, MaterialMode::Diffuse);
+        let (next_dir, brdf) = match mat.mode {
+            MaterialMode::Diffuse => {
+                let w = n;
+                let u = w.any_orthonormal().normalize();
+                let v_cross = w.cross(u);
+                let phi = 2.0 * PI * rng.gen::<f32>();
+                let r2: f32 = rng.gen();
+                ( (u * phi.cos() * r2.sqrt() + v_cross * phi.sin() * r2.sqrt() + w * (1.0 - r2).sqrt()).normalize(),
+                  mat.color * (1.0 / PI) )
+            }
+            MaterialMode::Mirror => {
+                (reflect(-v, n), mat.color)
+            }
+            MaterialMode::Glossy { specular_exponent } => {
+                // Map the Phong exponent to the GGX roughness that gives a
+                // lobe of similar width, then importance-sample the half
+                // vector from the *visible* normal distribution so no
+                // samples land on microfacets `v` couldn't see anyway.
+                let roughness = (2.0 / (specular_exponent + 2.0)).sqrt().clamp(0.01, 1.0);
+                let (h, pdf) = sample_ggx_vndf(v, n, roughness, rng);
+                let l = reflect(-v, h);
+                if pdf <= 0.0 || l.dot(n) <= 0.0 {
+                    (l, Vec3(0.0, 0.0, 0.0))
+                } else {
+                    // With a VNDF-sampled h, brdf*cos/pdf collapses to
+                    // F*(G2/G1) — the D and most of the G/pdf terms cancel
+                    // algebraically, which is the whole point of sampling
+                    // the visible normals instead of the full NDF. Divide
+                    // out n_dot_l here since the caller multiplies every
+                    // lobe's weight by `next_dir.dot(n)` uniformly below.
+                    let n_dot_v = v.dot(n).max(1e-4);
+                    let n_dot_l = l.dot(n).max(1e-4);
+                    let a = roughness * roughness;
+                    let g2 = g_term(n_dot_v, n_dot_l, a);
+                    let g1 = g1_term(n_dot_v, a);
+                    let f0 = Vec3(0.04, 0.04, 0.04) * (1.0 - mat.metallic) + mat.color * mat.metallic;
+                    let f = fresnel_schlick(v.dot(h).max(0.0), f0);
+                    (l, f.scale(g2 / (g1 * n_dot_l)))
+                }
+            }
+            MaterialMode::Dielectric => unreachable!("dielectric handled above"),
         };
 
         if next_dir.dot(n) > 0.0 {
-            let incoming = trace(hit + next_dir * 1e-4, next_dir, objs, lights, depth + 1, rng, next_media);
+            let incoming = trace(hit, next_dir, objs, bvh, lights, sky, depth + 1, rng, next_media, is_specular, time);
             indirect_light = incoming.mul(brdf).scale(next_dir.dot(n));
             if depth >= RUSSIAN_ROULETTE_DEPTH {
                 indirect_light = indirect_light.scale(1.0 / p);
@@ -341,16 +546,27 @@ pub fn trace(
         }
     }
 
-    return (direct_light + indirect_light).mul(absorption);
+    return (emitted + direct_light + indirect_light).mul(absorption);
 }
 
 
-fn intersect_closest(ro: Vec3, rd: Vec3, objs: &[Object])
+fn intersect_closest(ro: Vec3, rd: Vec3, objs: &[Object], bvh: &BvhNode, time: f32)
                      -> Option<(f32, Vec3, Material)>
 {
-    objs.iter()
-        .filter_map(|o| o.hit(ro, rd))
-        .min_by(|a, b| a.0.total_cmp(&b.0))
+    let inv_rd = Vec3(1.0 / rd.0, 1.0 / rd.1, 1.0 / rd.2);
+    let mut t_max = f32::INFINITY;
+    let mut best: Option<(f32, Vec3, Material)> = None;
+    bvh.traverse(ro, rd, inv_rd, T_MIN, &mut t_max, &mut |start, count, local_max| {
+        for o in &objs[start..start + count] {
+            if let Some((t, n, m)) = o.hit(ro, rd, time, T_MIN, local_max) {
+                if best.as_ref().map_or(true, |b| t < b.0) {
+                    best = Some((t, n, m));
+                }
+            }
+        }
+        best.as_ref().map(|b| b.0)
+    });
+    best
 }
 
 pub fn refract(v: Vec3, n: Vec3, eta_ratio: f32) -> Option<Vec3> {
@@ -1,9 +1,12 @@
 use serde::Deserialize;
 use std::collections::HashMap;
-use crate::{algebra::Vec3, material::Material, plane::Plane, sphere::Sphere, light::Light, algebra::vec3_from_array};
-use crate::mesh::{Mesh, Triangle};
-use tobj;
+use std::path::Path;
+use crate::{algebra::Vec3, material::{Material, MaterialMode}, plane::{Rect, InfinitePlane}, sphere::Sphere, light::Light, algebra::vec3_from_array};
 use crate::object::Object;
+use crate::bvh::{self, BvhNode};
+use crate::film::Filter;
+use crate::sky::Sky;
+use crate::tonemap::{Operator, ToneMapping};
 
 #[derive(Deserialize)]
 pub struct CameraJson {
@@ -15,9 +18,113 @@ pub struct CameraJson {
     pub up:       Vec3,
     pub fov:      f32,
     pub aperture: f32,
+    /// Shutter open/close time, in the same `[0,1)` units as `Sphere`
+    /// motion; defaults to a zero-length interval (no motion blur).
+    #[serde(default)]
+    pub shutter0: f32,
+    #[serde(default)]
+    pub shutter1: f32,
+}
+#[derive(Deserialize)]
+pub struct RenderJson {
+    pub width:u32,
+    pub height:u32,
+    pub samples:u32,
+    /// Pixel reconstruction filter; defaults to a box filter (plain
+    /// sample averaging) so existing scenes render unchanged.
+    #[serde(default)]
+    pub filter: FilterJson,
+    /// Tone-mapping operator, exposure and gamma applied to linear radiance
+    /// before quantizing to 8 bits; defaults to the ACES fit at neutral
+    /// exposure/gamma this renderer always used.
+    #[serde(default)]
+    pub tonemap: TonemapJson,
+}
+
+/// Mirrors `MaterialModeJson`'s externally-tagged-enum shape for the tone
+/// curve.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TonemapOperatorJson {
+    Reinhard,
+    ReinhardExtended { white: f32 },
+    Aces,
+    None,
+}
+
+impl Default for TonemapOperatorJson {
+    fn default() -> Self { TonemapOperatorJson::Aces }
 }
+
+fn default_gamma() -> f32 { 2.2 }
+fn default_exposure() -> f32 { 1.0 }
+
 #[derive(Deserialize)]
-pub struct RenderJson { pub width:u32, pub height:u32, pub samples:u32 }
+pub struct TonemapJson {
+    #[serde(default)]
+    pub operator: TonemapOperatorJson,
+    #[serde(default = "default_exposure")]
+    pub exposure: f32,
+    #[serde(default = "default_gamma")]
+    pub gamma: f32,
+}
+
+impl Default for TonemapJson {
+    fn default() -> Self {
+        Self { operator: TonemapOperatorJson::default(), exposure: default_exposure(), gamma: default_gamma() }
+    }
+}
+
+impl TonemapJson {
+    pub fn resolve(&self) -> ToneMapping {
+        let operator = match self.operator {
+            TonemapOperatorJson::Reinhard => Operator::Reinhard,
+            TonemapOperatorJson::ReinhardExtended { white } => Operator::ReinhardExtended { white },
+            TonemapOperatorJson::Aces => Operator::Aces,
+            TonemapOperatorJson::None => Operator::None,
+        };
+        ToneMapping { operator, exposure: self.exposure, gamma: self.gamma }
+    }
+}
+
+/// Which reconstruction kernel to splat samples with, mirroring
+/// `MaterialModeJson`'s externally-tagged-enum shape.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterKindJson {
+    Box,
+    Tent,
+    Gaussian { alpha: f32 },
+    Mitchell { b: f32, c: f32 },
+}
+
+impl Default for FilterKindJson {
+    fn default() -> Self { FilterKindJson::Box }
+}
+
+#[derive(Deserialize, Default)]
+pub struct FilterJson {
+    #[serde(default)]
+    pub kind: FilterKindJson,
+    /// Filter support radius, in pixels; defaults to a radius suited to
+    /// `kind` when omitted.
+    #[serde(default)]
+    pub radius: Option<f32>,
+}
+
+impl FilterJson {
+    /// Converts the JSON description into the renderer's domain [`Filter`]
+    /// plus the radius to splat it over.
+    pub fn resolve(&self) -> (Filter, f32) {
+        let (filter, default_radius) = match self.kind {
+            FilterKindJson::Box => (Filter::Box, 0.5),
+            FilterKindJson::Tent => (Filter::Tent, 1.0),
+            FilterKindJson::Gaussian { alpha } => (Filter::Gaussian { alpha }, 2.0),
+            FilterKindJson::Mitchell { b, c } => (Filter::Mitchell { b, c }, 2.0),
+        };
+        (filter, self.radius.unwrap_or(default_radius))
+    }
+}
 
 #[derive(Deserialize)] struct MaterialJson {
     rgb:[f32;3],
@@ -28,6 +135,30 @@ pub struct RenderJson { pub width:u32, pub height:u32, pub samples:u32 }
     volume_density: f32,
     #[serde(default)]
     volume_anisotropy: f32,
+    /// Emitted radiance; defaults to black so existing scenes are unaffected.
+    #[serde(default)]
+    emission: [f32; 3],
+    /// How this material scatters light on an indirect bounce; defaults to
+    /// plain Lambertian diffuse so existing scenes render unchanged.
+    #[serde(default)]
+    mode: MaterialModeJson,
+    /// Image sampled in place of `rgb` wherever the hit geometry carries
+    /// UVs; absent for a flat-colored material.
+    #[serde(default)]
+    texture: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MaterialModeJson {
+    Diffuse,
+    Glossy { specular_exponent: f32 },
+    Mirror,
+    Dielectric,
+}
+
+impl Default for MaterialModeJson {
+    fn default() -> Self { MaterialModeJson::Diffuse }
 }
 
 
@@ -36,14 +167,29 @@ pub struct RenderJson { pub width:u32, pub height:u32, pub samples:u32 }
 enum ObjectJson {
     Sphere{ sphere: SphereDesc },
     Plane { plane : PlaneDesc  },
+    InfinitePlane { infinite_plane: InfinitePlaneDesc },
     Mesh  { mesh  : MeshDesc   },
 }
 
+fn default_time0() -> f32 { 0.0 }
+fn default_time1() -> f32 { 1.0 }
+
 #[derive(Deserialize)]
 pub struct SphereDesc {
     pub name:   String,
     #[serde(deserialize_with = "vec3_from_array")]
     pub center: Vec3,
+    /// Optional end-of-shutter center; when present the sphere moves from
+    /// `center` to `center1` over `time0..time1` (motion blur).
+    #[serde(default)]
+    pub center1: Option<[f32; 3]>,
+    /// The sphere's own motion window; defaults to the full `[0,1)` shutter
+    /// range so it lines up with `camera.shutter0..shutter1` unless a scene
+    /// wants the sphere to start or stop moving partway through exposure.
+    #[serde(default = "default_time0")]
+    pub time0: f32,
+    #[serde(default = "default_time1")]
+    pub time1: f32,
     pub radius: f32,
     pub mat:    String,
     #[serde(default)] // Default to false if not present in JSON
@@ -63,6 +209,18 @@ pub struct PlaneDesc {
     pub in_focus: bool,
 }
 
+#[derive(Deserialize)]
+pub struct InfinitePlaneDesc {
+    pub name:   String,
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub point : Vec3,
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub normal: Vec3,
+    pub mat   : String,
+    #[serde(default)]
+    pub in_focus: bool,
+}
+
 #[derive(Deserialize)]
 pub struct MeshDesc {
     pub name: String,
@@ -74,7 +232,52 @@ pub struct MeshDesc {
 
 
 #[derive(Deserialize)]
-pub struct LightJson {
+#[serde(untagged)]
+enum LightJson {
+    Point { point: PointLightDesc },
+    Spot { spot: SpotLightDesc },
+    Directional { directional: DirectionalLightDesc },
+    Area { area: AreaLightDesc },
+}
+
+#[derive(Deserialize)]
+pub struct PointLightDesc {
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub pos:       Vec3,
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub intensity: Vec3,
+    #[serde(default)]
+    pub radius:    f32,
+    #[serde(default)]
+    pub influence: Option<f32>,
+}
+
+#[derive(Deserialize)]
+pub struct SpotLightDesc {
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub pos:       Vec3,
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub dir:       Vec3,
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub intensity: Vec3,
+    /// Half-angle of the full (outer) cone, in degrees, measured from `dir`.
+    pub size:      f32,
+    /// Blend angle, in degrees, measured inward from `size`.
+    pub blend:     f32,
+    #[serde(default)]
+    pub influence: Option<f32>,
+}
+
+#[derive(Deserialize)]
+pub struct DirectionalLightDesc {
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub dir:       Vec3,
+    #[serde(deserialize_with = "vec3_from_array")]
+    pub intensity: Vec3,
+}
+
+#[derive(Deserialize)]
+pub struct AreaLightDesc {
     #[serde(deserialize_with = "vec3_from_array")]
     pub pos:       Vec3,
     #[serde(deserialize_with = "vec3_from_array")]
@@ -93,6 +296,38 @@ struct SceneFile {
     materials: HashMap<String, MaterialJson>,
     objects  : Vec<ObjectJson>,
     lights   : Vec<LightJson>,
+    /// Radiance seen by rays that escape the scene; defaults to a flat
+    /// black gradient so existing scenes render with the same plain
+    /// background as before.
+    #[serde(default)]
+    sky: SkyJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SkyJson {
+    Gradient {
+        #[serde(deserialize_with = "vec3_from_array")]
+        bottom: Vec3,
+        #[serde(deserialize_with = "vec3_from_array")]
+        top: Vec3,
+    },
+    Hdr { file: String },
+}
+
+impl Default for SkyJson {
+    fn default() -> Self {
+        SkyJson::Gradient { bottom: Vec3(0.0, 0.0, 0.0), top: Vec3(0.0, 0.0, 0.0) }
+    }
+}
+
+impl SkyJson {
+    fn load(self) -> Sky {
+        match self {
+            SkyJson::Gradient { bottom, top } => Sky::Gradient { bottom, top },
+            SkyJson::Hdr { file } => Sky::Hdr(crate::sky::load_hdr(&file)),
+        }
+    }
 }
 
 /// Public “loaded” scene
@@ -101,14 +336,35 @@ pub struct Scene {
     pub render : RenderJson,
     pub objects: Vec<crate::object::Object>,
     pub lights : Vec<Light>,
+    /// Top-level BVH over `objects`, in the same index order (objects are
+    /// reordered into BVH-leaf order once at load time).
+    pub bvh    : BvhNode,
+    /// Radiance returned by rays that miss every object.
+    pub sky    : Sky,
 }
 
-pub fn load(path:&str) -> Scene {
+/// Loads a scene from `path`. `.gltf`/`.glb` files are imported via
+/// `gltf_import`; everything else is parsed as the native JSON format below.
+pub fn load(path: &str) -> Scene {
+    let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+    if ext.eq_ignore_ascii_case("gltf") || ext.eq_ignore_ascii_case("glb") {
+        return crate::gltf_import::load(path);
+    }
+    load_json(path)
+}
+
+fn load_json(path:&str) -> Scene {
     let data = std::fs::read_to_string(path).expect("scene file");
     let file : SceneFile = serde_json::from_str(&data).expect("json parse");
 
     // 1. Create a library of materials from the JSON
     let materials: HashMap<String, Material> = file.materials.into_iter().map(|(name, m)| {
+        let mode = match m.mode {
+            MaterialModeJson::Diffuse => MaterialMode::Diffuse,
+            MaterialModeJson::Glossy { specular_exponent } => MaterialMode::Glossy { specular_exponent },
+            MaterialModeJson::Mirror => MaterialMode::Mirror,
+            MaterialModeJson::Dielectric => MaterialMode::Dielectric,
+        };
         let mat = Material {
             color: Vec3(m.rgb[0], m.rgb[1], m.rgb[2]),
             metallic: m.metallic,
@@ -117,6 +373,10 @@ pub fn load(path:&str) -> Scene {
             // --- NEW: Assign volume properties ---
             volume_density: m.volume_density,
             volume_anisotropy: m.volume_anisotropy,
+            emission: Vec3(m.emission[0], m.emission[1], m.emission[2]),
+            light_sampled: false,
+            mode,
+            albedo_texture: m.texture.as_deref().map(crate::texture::Texture::load),
         };
         (name, mat)
     }).collect();
@@ -128,7 +388,11 @@ pub fn load(path:&str) -> Scene {
         ior: 1.0,
         // --- NEW ---
         volume_density: 0.0,
-        volume_anisotropy: 0.0
+        volume_anisotropy: 0.0,
+        emission: Vec3(0.0, 0.0, 0.0),
+        light_sampled: false,
+        mode: MaterialMode::Diffuse,
+        albedo_texture: None,
     };
 
 
@@ -141,6 +405,9 @@ pub fn load(path:&str) -> Scene {
                 objects.push(Object::Sphere(Sphere {
                     name:     sphere.name,
                     center:   sphere.center,
+                    center1:  sphere.center1.map(Vec3::from),
+                    time0:    sphere.time0,
+                    time1:    sphere.time1,
                     radius:   sphere.radius,
                     material,
                     in_focus: sphere.in_focus, // ADDED
@@ -149,7 +416,7 @@ pub fn load(path:&str) -> Scene {
             ObjectJson::Plane { plane } => {
                 let material = *materials.get(&plane.mat).unwrap_or(&default_mat);
                 let normal = plane.u.cross(plane.v).normalize();
-                objects.push(Object::Plane(Plane {
+                objects.push(Object::Rect(Rect {
                     name:     plane.name,
                     point:    plane.point,
                     u:        plane.u,
@@ -159,42 +426,85 @@ pub fn load(path:&str) -> Scene {
                     in_focus: plane.in_focus,
                 }));
             }
+            ObjectJson::InfinitePlane { infinite_plane } => {
+                let material = *materials.get(&infinite_plane.mat).unwrap_or(&default_mat);
+                objects.push(Object::InfinitePlane(InfinitePlane {
+                    name:     infinite_plane.name,
+                    point:    infinite_plane.point,
+                    normal:   infinite_plane.normal.normalize(),
+                    material,
+                    in_focus: infinite_plane.in_focus,
+                }));
+            }
             ObjectJson::Mesh { mesh } => {
-                let material = *materials.get(&mesh.mat).unwrap_or(&default_mat);
-                let mut triangles = Vec::new();
-                let (models, _mats) = tobj::load_obj(&mesh.file, &tobj::LoadOptions::default()).expect("load obj");
-                for m in models {
-                    let mesh_data = &m.mesh;
-                    for idx in (0..mesh_data.indices.len()).step_by(3) {
-                        let i0 = mesh_data.indices[idx] as usize;
-                        let i1 = mesh_data.indices[idx+1] as usize;
-                        let i2 = mesh_data.indices[idx+2] as usize;
-                        let p0 = Vec3(
-                            mesh_data.positions[3*i0],
-                            mesh_data.positions[3*i0+1],
-                            mesh_data.positions[3*i0+2],
-                        );
-                        let p1 = Vec3(
-                            mesh_data.positions[3*i1],
-                            mesh_data.positions[3*i1+1],
-                            mesh_data.positions[3*i1+2],
-                        );
-                        let p2 = Vec3(
-                            mesh_data.positions[3*i2],
-                            mesh_data.positions[3*i2+1],
-                            mesh_data.positions[3*i2+2],
-                        );
-                        triangles.push(Triangle { v0:p0, v1:p1, v2:p2, material });
-                    }
+                // `mesh.mat` is the fallback for faces with no `usemtl`/no
+                // `mtllib` at all; groups that do carry their own material
+                // via the OBJ's `.mtl` file keep that instead.
+                let fallback = *materials.get(&mesh.mat).unwrap_or(&default_mat);
+                let groups = crate::mesh::load_obj(&mesh.file, fallback)
+                    .unwrap_or_else(|e| panic!("failed to load mesh '{}' from {}: {e}", mesh.name, mesh.file));
+                for mut m in groups {
+                    m.name = format!("{}/{}", mesh.name, m.name);
+                    m.in_focus = mesh.in_focus;
+                    objects.push(Object::Mesh(m));
                 }
-                objects.push(Object::Mesh(Mesh { name: mesh.name, triangles, in_focus: mesh.in_focus }));
             }
         }
     }
 
-    let lights = file.lights.iter().map(|l| Light{
-        pos:l.pos, u:l.u, v:l.v, intensity:l.intensity
+    let mut lights: Vec<Light> = file.lights.into_iter().map(|l| match l {
+        LightJson::Point { point } => Light::Point {
+            pos: point.pos,
+            intensity: point.intensity,
+            radius: point.radius,
+            influence: point.influence,
+        },
+        LightJson::Spot { spot } => Light::Spot {
+            pos: spot.pos,
+            dir: spot.dir.normalize(),
+            intensity: spot.intensity,
+            cos_size: spot.size.to_radians().cos(),
+            cos_blend: (spot.size - spot.blend).to_radians().cos(),
+            influence: spot.influence,
+        },
+        LightJson::Directional { directional } => Light::Directional {
+            dir: directional.dir.normalize(),
+            intensity: directional.intensity,
+        },
+        LightJson::Area { area } => Light::Area {
+            pos: area.pos, u: area.u, v: area.v, intensity: area.intensity,
+        },
     }).collect();
 
-    Scene{ camera:file.camera, render:file.render, objects, lights }
+    // 2b. Any emissive Rect is also an implicit area light: fold it into
+    // `lights` so `direct_light_sample` next-event-estimates it (zero-variance
+    // diffuse term, sampled specular term) the same as an explicit `area`
+    // light, and mark its material `light_sampled` so `trace` doesn't also
+    // add its emission unconditionally and double-count it. Sphere/Mesh/
+    // InfinitePlane emissive surfaces have no analytic area-light sampling
+    // in this renderer yet, so they keep being picked up by `trace`'s
+    // unconditional emission add instead — noisier, but not biased.
+    for obj in objects.iter_mut() {
+        if let Object::Rect(r) = obj {
+            let e = r.material.emission;
+            if e.0 > 0.0 || e.1 > 0.0 || e.2 > 0.0 {
+                // A Rect's u/v run from its center to an edge; an area
+                // light's u/v span the full edge.
+                lights.push(Light::Area { pos: r.point, u: r.u.scale(2.0), v: r.v.scale(2.0), intensity: e });
+                r.material.light_sampled = true;
+            }
+        }
+    }
+
+    // 3. Build a top-level BVH over the objects, reordering them into
+    // BVH-leaf order so the flat `objects` vec stays the traversal's
+    // primitive storage (one object per leaf slot).
+    let aabbs: Vec<bvh::Aabb> = objects.iter().map(Object::aabb).collect();
+    let mut indices: Vec<usize> = (0..objects.len()).collect();
+    let bvh = bvh::build(&aabbs, &mut indices);
+    let objects: Vec<Object> = indices.into_iter().map(|i| objects[i].clone()).collect();
+
+    let sky = file.sky.load();
+
+    Scene{ camera:file.camera, render:file.render, objects, lights, bvh, sky }
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+//! The radiance seen by rays that escape the scene entirely: either a cheap
+//! procedural gradient, or an HDR equirectangular environment map that can
+//! also be importance-sampled for next-event estimation (image-based
+//! lighting).
+
+use crate::algebra::Vec3;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// A distant light source with no geometry of its own.
+pub enum Sky {
+    /// Vertical gradient lerped by the ray direction's `y` component — a
+    /// soft, physically-plausible stand-in for sky ambient with no texture
+    /// to load.
+    Gradient { bottom: Vec3, top: Vec3 },
+    /// An equirectangular HDR environment map, importance-sampled via a 2D
+    /// CDF over its texel luminance.
+    Hdr(EnvMap),
+}
+
+impl Sky {
+    /// Radiance seen along normalized direction `d`.
+    pub fn radiance(&self, d: Vec3) -> Vec3 {
+        match self {
+            Sky::Gradient { bottom, top } => bottom.lerp(*top, 0.5 * (d.1 + 1.0)),
+            Sky::Hdr(env) => env.radiance(d),
+        }
+    }
+
+    /// Draws a direction toward the sky for next-event estimation, returning
+    /// `(direction, radiance, pdf_solid_angle)`. The gradient sky has no
+    /// bright spots to bias toward, so it falls back to uniform-sphere
+    /// sampling; the HDR map draws from its luminance CDF.
+    pub fn sample(&self, rng: &mut impl Rng) -> (Vec3, Vec3, f32) {
+        match self {
+            Sky::Gradient { .. } => {
+                let z = 1.0 - 2.0 * rng.gen::<f32>();
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let phi = 2.0 * PI * rng.gen::<f32>();
+                let d = Vec3(r * phi.cos(), z, r * phi.sin());
+                (d, self.radiance(d), 1.0 / (4.0 * PI))
+            }
+            Sky::Hdr(env) => env.sample(rng),
+        }
+    }
+}
+
+/// An equirectangular HDR texture plus the 2D CDF used to importance-sample
+/// it. `u = 0.5 + atan2(d.z, d.x) / (2*pi)`, `v = acos(d.y) / pi`, matching
+/// the usual Blender/PBR environment-map convention.
+pub struct EnvMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+    /// CDF of each row's total (luminance * sin(theta)) weight, length
+    /// `height + 1`, normalized to `[0, 1]`.
+    marginal_cdf: Vec<f32>,
+    /// Per-row CDF over columns, length `height * (width + 1)`, normalized
+    /// to `[0, 1]` within each row.
+    conditional_cdf: Vec<f32>,
+    total_weight: f32,
+}
+
+impl EnvMap {
+    /// Builds the importance-sampling CDFs over `pixels` (row-major,
+    /// `width * height` texels of linear radiance).
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> Self {
+        let mut conditional_cdf = vec![0.0f32; height * (width + 1)];
+        let mut row_weights = vec![0.0f32; height];
+
+        for y in 0..height {
+            // sin(theta) weights rows toward the equator, correcting for the
+            // equirectangular projection's compression at the poles.
+            let theta = PI * (y as f32 + 0.5) / height as f32;
+            let sin_theta = theta.sin();
+            let row = &pixels[y * width..(y + 1) * width];
+            let base = y * (width + 1);
+            let mut acc = 0.0f32;
+            for (x, texel) in row.iter().enumerate() {
+                let luminance = texel.0 * 0.2126 + texel.1 * 0.7152 + texel.2 * 0.0722;
+                acc += (luminance * sin_theta).max(0.0);
+                conditional_cdf[base + x + 1] = acc;
+            }
+            row_weights[y] = acc;
+            if acc > 0.0 {
+                for x in 0..width {
+                    conditional_cdf[base + x + 1] /= acc;
+                }
+            }
+        }
+
+        let mut marginal_cdf = vec![0.0f32; height + 1];
+        let mut acc = 0.0f32;
+        for y in 0..height {
+            acc += row_weights[y];
+            marginal_cdf[y + 1] = acc;
+        }
+        let total_weight = acc;
+        if total_weight > 0.0 {
+            for v in marginal_cdf.iter_mut() {
+                *v /= total_weight;
+            }
+        }
+
+        Self { width, height, pixels, marginal_cdf, conditional_cdf, total_weight }
+    }
+
+    fn texel(&self, x: usize, y: usize) -> Vec3 {
+        self.pixels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+
+    fn direction_to_uv(d: Vec3) -> (f32, f32) {
+        let u = 0.5 + d.2.atan2(d.0) / (2.0 * PI);
+        let v = d.1.clamp(-1.0, 1.0).acos() / PI;
+        (u, v)
+    }
+
+    pub fn radiance(&self, d: Vec3) -> Vec3 {
+        let (u, v) = Self::direction_to_uv(d);
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.texel(x, y)
+    }
+
+    /// Binary-searches a CDF for the interval containing `xi`, returning
+    /// its index.
+    fn invert_cdf(cdf: &[f32], xi: f32) -> usize {
+        match cdf.binary_search_by(|v| v.partial_cmp(&xi).unwrap()) {
+            Ok(i) | Err(i) => i.saturating_sub(1).min(cdf.len().saturating_sub(2)),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> (Vec3, Vec3, f32) {
+        if self.total_weight <= 0.0 {
+            // No energy anywhere in the map; fall back to uniform sampling
+            // rather than dividing by a zero PDF.
+            let z = 1.0 - 2.0 * rng.gen::<f32>();
+            let r = (1.0 - z * z).max(0.0).sqrt();
+            let phi = 2.0 * PI * rng.gen::<f32>();
+            let d = Vec3(r * phi.cos(), z, r * phi.sin());
+            return (d, self.radiance(d), 1.0 / (4.0 * PI));
+        }
+
+        let y = Self::invert_cdf(&self.marginal_cdf, rng.gen::<f32>());
+        let row = &self.conditional_cdf[y * (self.width + 1)..(y + 1) * (self.width + 1)];
+        let x = Self::invert_cdf(row, rng.gen::<f32>());
+
+        let u = (x as f32 + 0.5) / self.width as f32;
+        let v = (y as f32 + 0.5) / self.height as f32;
+        let theta = v * PI;
+        let phi = (u - 0.5) * 2.0 * PI;
+        let sin_theta = theta.sin();
+        let d = Vec3(sin_theta * phi.cos(), theta.cos(), sin_theta * phi.sin());
+
+        let radiance = self.texel(x, y);
+        let luminance = radiance.0 * 0.2126 + radiance.1 * 0.7152 + radiance.2 * 0.0722;
+        // pdf(u,v) * |d(u,v)/d(omega)| = pdf(u,v) * width*height / (2*pi^2*sin(theta))
+        let pixel_pdf = (luminance * sin_theta).max(1e-8) / self.total_weight
+            * (self.width * self.height) as f32;
+        let pdf_solid_angle = pixel_pdf / (2.0 * PI * PI * sin_theta.max(1e-4));
+
+        (d, radiance, pdf_solid_angle.max(1e-8))
+    }
+}
+
+/// Loads an equirectangular HDR image from `path` into an [`EnvMap`].
+pub fn load_hdr(path: &str) -> EnvMap {
+    let img = image::open(path).expect("hdr environment map").to_rgb32f();
+    let (width, height) = img.dimensions();
+    let pixels: Vec<Vec3> = img.pixels().map(|p| Vec3(p[0], p[1], p[2])).collect();
+    EnvMap::new(width as usize, height as usize, pixels)
+}
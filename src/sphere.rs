@@ -4,37 +4,81 @@
 
 use std::ops::{Add, Sub};
 use crate::{algebra::Vec3, material::Material};
+use crate::bvh::Aabb;
 
 #[derive(Clone, Debug)] // MODIFIED: Removed Copy trait
 pub struct Sphere {
     pub name     : String, // ADDED
     pub center   : Vec3,
+    /// End-of-shutter center for motion blur; `None` for a static sphere.
+    pub center1  : Option<Vec3>,
+    /// Time at which the sphere is at `center` and `center1` respectively,
+    /// in the same units as the ray `time` passed to `hit`. Lets a sphere's
+    /// own motion window differ from the camera's `shutter0..shutter1`
+    /// (e.g. starting to move partway through the exposure).
+    pub time0    : f32,
+    pub time1    : f32,
     pub radius   : f32,
     pub material : Material,
     pub in_focus : bool, // ADDED
 }
 
 impl Sphere {
-    /// Intersect a ray (ro + t·rd).
-    /// Returns *closest positive* hit: (t, surface_normal, material).
+    /// Center of the sphere at shutter-relative `time`; lerps between
+    /// `center`/`center1` over `time0..time1`, clamped at either end.
+    fn center_at(&self, time: f32) -> Vec3 {
+        match self.center1 {
+            Some(c1) => {
+                let frac = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+                self.center.lerp(c1, frac)
+            }
+            None => self.center,
+        }
+    }
+
+    /// Intersect a ray (ro + t·rd) at the given shutter `time`, restricted to
+    /// `t` in `(t_min, t_max)`. Checks both quadratic roots so a hit just
+    /// behind `t_min` (e.g. the surface a shadow ray is leaving) doesn't
+    /// shadow the farther, correct root.
+    /// Returns the nearest in-range hit: (t, surface_normal, material).
     pub fn hit(&self,
                ro: Vec3,
-               rd: Vec3)
+               rd: Vec3,
+               time: f32,
+               t_min: f32,
+               t_max: f32)
                -> Option<(f32, Vec3, Material)>
     {
+        let center = self.center_at(time);
+
         // Analytic quadratic
-        let oc   = ro.sub(self.center);
+        let oc   = ro.sub(center);
         let a    = rd.dot(rd);
         let b    = 2.0 * oc.dot(rd);
         let c    = oc.dot(oc) - self.radius * self.radius;
         let disc = b*b - 4.0*a*c;
         if disc < 0.0 { return None; }
 
-        let t = (-b - disc.sqrt()) / (2.0 * a);
-        if t <= 0.0 { return None; }
+        let sqrt_disc = disc.sqrt();
+        let mut t = (-b - sqrt_disc) / (2.0 * a);
+        if t <= t_min || t >= t_max {
+            t = (-b + sqrt_disc) / (2.0 * a);
+            if t <= t_min || t >= t_max { return None; }
+        }
 
         let hit     = ro.add(rd.scale(t));
-        let normal  = hit.sub(self.center).scale(1.0 / self.radius); // unit
+        let normal  = hit.sub(center).scale(1.0 / self.radius); // unit
         Some((t, normal, self.material))
     }
+
+    /// Bounding box spanning the full motion range so the BVH doesn't prune
+    /// the sphere out at either end of the shutter.
+    pub fn aabb(&self) -> Aabb {
+        let r = Vec3(self.radius, self.radius, self.radius);
+        let mut b = Aabb::from_points(self.center.sub(r), self.center.add(r));
+        if let Some(c1) = self.center1 {
+            b = b.union(Aabb::from_points(c1.sub(r), c1.add(r)));
+        }
+        b
+    }
 }
\ No newline at end of file
@@ -0,0 +1,64 @@
+//! A loaded RGB image sampled with bilinear filtering, used by
+//! [`crate::material::Material`] to vary a surface's albedo across its UV
+//! footprint instead of a single flat color.
+
+use crate::algebra::Vec3;
+
+/// How [`Texture::sample`] handles a UV coordinate outside `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WrapMode {
+    /// Tiles the image, `1.1` sampling the same texel as `0.1`.
+    Repeat,
+    /// Extends the edge texel outward past `0` and `1`.
+    Clamp,
+}
+
+#[derive(Debug)]
+pub struct Texture {
+    width: usize,
+    height: usize,
+    /// Row-major linear-color texels, top row first.
+    pixels: Vec<Vec3>,
+    pub wrap: WrapMode,
+}
+
+impl Texture {
+    /// Loads `path` into a `Repeat`-wrapped texture, leaked to `'static` so
+    /// a [`Material`](crate::material::Material) can hold a plain reference
+    /// and stay `Copy` — scenes are loaded once and live for the process's
+    /// whole run, same lifetime a leak gives the texture.
+    pub fn load(path: &str) -> &'static Texture {
+        let img = image::open(path).expect("texture image").to_rgb32f();
+        let (width, height) = img.dimensions();
+        let pixels: Vec<Vec3> = img.pixels().map(|p| Vec3(p[0], p[1], p[2])).collect();
+        Box::leak(Box::new(Self { width: width as usize, height: height as usize, pixels, wrap: WrapMode::Repeat }))
+    }
+
+    fn wrap_index(&self, i: i64, size: usize) -> usize {
+        match self.wrap {
+            WrapMode::Repeat => i.rem_euclid(size as i64) as usize,
+            WrapMode::Clamp => i.clamp(0, size as i64 - 1) as usize,
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let x = self.wrap_index(x, self.width);
+        let y = self.wrap_index(y, self.height);
+        self.pixels[y * self.width + x]
+    }
+
+    /// Bilinearly samples the texture at UV `(u, v)`; `v = 0` is the
+    /// image's bottom row and `v = 1` its top row, the OBJ/OpenGL
+    /// texture-coordinate convention.
+    pub fn sample(&self, u: f32, v: f32) -> Vec3 {
+        let x = u * self.width as f32 - 0.5;
+        let y = (1.0 - v) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0).scale(1.0 - fx) + self.texel(x0 + 1, y0).scale(fx);
+        let bottom = self.texel(x0, y0 + 1).scale(1.0 - fx) + self.texel(x0 + 1, y0 + 1).scale(fx);
+        top.scale(1.0 - fy) + bottom.scale(fy)
+    }
+}
@@ -0,0 +1,144 @@
+//! Tiled, pass-based render coordinator.
+//!
+//! Splits the frame into fixed-size tiles and renders them in passes: each
+//! pass pushes every tile onto rayon's work queue for one small batch of
+//! samples, workers pull tiles and splat their samples onto the shared
+//! [`Film`], and once a pass finishes over all tiles the running filtered
+//! mean is flushed into the output image. This decouples the total sample
+//! count from a single blocking pass — the caller sees a rapidly-refining
+//! preview after every pass and can stop early — and load-balances far
+//! better than whole rows when scene complexity varies across the image.
+
+use crate::{
+    algebra::Vec3,
+    bvh::BvhNode,
+    film::{Film, Filter},
+    light::Light,
+    object::Object,
+    renderer::{sample_radiance, tonemap_to_rgb8},
+    sky::Sky,
+    tonemap::ToneMapping,
+};
+use image::{Rgb, RgbImage};
+use rand::{thread_rng, Rng};
+use rayon::prelude::*;
+
+/// Tile edge length, in pixels.
+pub const TILE_SIZE: u32 = 32;
+
+/// One rectangular region of the frame, in pixel coordinates `[x0, x1) x
+/// [y0, y1)`.
+#[derive(Clone, Copy)]
+pub struct Tile {
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+}
+
+/// Splits a `width`×`height` frame into fixed-size [`TILE_SIZE`] tiles, with
+/// a partial tile along the right/bottom edge when the dimensions aren't a
+/// multiple of it.
+pub fn tiles_for(width: u32, height: u32) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    let mut y0 = 0;
+    while y0 < height {
+        let y1 = (y0 + TILE_SIZE).min(height);
+        let mut x0 = 0;
+        while x0 < width {
+            let x1 = (x0 + TILE_SIZE).min(width);
+            tiles.push(Tile { x0, y0, x1, y1 });
+            x0 = x1;
+        }
+        y0 = y1;
+    }
+    tiles
+}
+
+/// Camera and scene state needed to trace a sample, bundled so [`render`]
+/// doesn't need a dozen positional parameters.
+pub struct RenderParams<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub samples: u32,
+    pub samples_per_pass: u32,
+    pub filter: Filter,
+    pub filter_radius: f32,
+    pub tonemapping: ToneMapping,
+    pub aspect: f32,
+    pub scale: f32,
+    pub cam: Vec3,
+    pub right: Vec3,
+    pub up: Vec3,
+    pub forward: Vec3,
+    pub focus: f32,
+    pub aperture: f32,
+    pub shutter0: f32,
+    pub shutter1: f32,
+    pub objs: &'a [Object],
+    pub bvh: &'a BvhNode,
+    pub lights: &'a [Light],
+    pub sky: &'a Sky,
+}
+
+/// The finished render: a tonemapped, quantized image ready to save as a
+/// standard LDR format, and the raw linear radiance behind it for a
+/// lossless HDR master.
+pub struct RenderOutput {
+    pub image: RgbImage,
+    pub linear: Vec<Vec3>,
+}
+
+/// Renders `params.samples` samples per pixel in passes of
+/// `params.samples_per_pass`, calling `on_pass(pass, samples_done, &img)`
+/// after every pass with the image flushed to the filtered mean-so-far.
+pub fn render(params: &RenderParams, mut on_pass: impl FnMut(u32, u32, &RgbImage)) -> RenderOutput {
+    let tiles = tiles_for(params.width, params.height);
+    let film = Film::new(params.width, params.height, params.filter, params.filter_radius);
+
+    let mut img = RgbImage::new(params.width, params.height);
+    let mut samples_done = 0;
+    let mut pass = 0;
+    while samples_done < params.samples {
+        let this_pass = params.samples_per_pass.min(params.samples - samples_done);
+
+        // One pass: every tile is a unit of work on rayon's queue, so tiles
+        // that land on cheap (empty-sky) or expensive (dense-geometry)
+        // regions of the frame don't block each other. A sample's footprint
+        // can spill past its own tile's border, so it's splatted onto the
+        // frame-wide `film` rather than a tile-local buffer.
+        tiles.par_iter().for_each(|t| {
+            let mut rng = thread_rng();
+            for y in t.y0..t.y1 {
+                for x in t.x0..t.x1 {
+                    for _ in 0..this_pass {
+                        let jx = rng.gen::<f32>();
+                        let jy = rng.gen::<f32>();
+                        let color = sample_radiance(
+                            x, y, params.width, params.height, jx, jy, params.aspect, params.scale,
+                            params.cam, params.right, params.up, params.forward,
+                            params.focus, params.aperture, params.shutter0, params.shutter1,
+                            params.objs, params.bvh, params.lights, params.sky, &mut rng,
+                        );
+                        film.add_sample(x as f32 + jx, y as f32 + jy, color);
+                    }
+                }
+            }
+        });
+        samples_done += this_pass;
+        pass += 1;
+
+        for y in 0..params.height {
+            for x in 0..params.width {
+                img.put_pixel(x, y, Rgb(tonemap_to_rgb8(film.mean(x, y), &params.tonemapping)));
+            }
+        }
+        on_pass(pass, samples_done, &img);
+    }
+
+    let linear = (0..params.height)
+        .flat_map(|y| (0..params.width).map(move |x| (x, y)))
+        .map(|(x, y)| film.mean(x, y))
+        .collect();
+    RenderOutput { image: img, linear }
+}
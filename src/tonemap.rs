@@ -6,6 +6,15 @@ pub fn reinhard(c: Vec3) -> Vec3 {
         c.2 / (1.0 + c.2),
     )
 }
+
+/// Reinhard's extended operator: like [`reinhard`], but highlights roll off
+/// toward `white` instead of asymptoting to 1 no matter how bright the
+/// input is — `c · (1 + c/white²) / (1 + c)`.
+pub fn reinhard_extended(c: Vec3, white: f32) -> Vec3 {
+    let white2 = white * white;
+    c.map(|x| (x * (1.0 + x / white2)) / (1.0 + x))
+}
+
 #[inline]
 pub fn aces_film(c: Vec3) -> Vec3 {
     let a = 2.51;
@@ -16,4 +25,53 @@ pub fn aces_film(c: Vec3) -> Vec3 {
 
     // Use the map function we created for Vec3
     c.map(|x| ((x * (a * x + b)) / (x * (c2 * x + d) + e)).clamp(0.0, 1.0))
-}
\ No newline at end of file
+}
+
+/// Which tone curve compresses linear HDR radiance into displayable range.
+#[derive(Clone, Copy, Debug)]
+pub enum Operator {
+    Reinhard,
+    /// [`reinhard_extended`]'s roll-off toward a configurable white point.
+    ReinhardExtended { white: f32 },
+    /// The Narkowicz ACES filmic fit used by `aces_film`.
+    Aces,
+    /// No tone curve at all; radiance is only exposed, clamped and gamma
+    /// encoded. Useful for scenes that never leave `[0, 1]` or for
+    /// comparing against the untonemapped EXR master.
+    None,
+}
+
+/// The full display pipeline applied to a pixel's mean linear radiance
+/// before it's quantized to 8 bits: exposure, then the tone curve, then
+/// gamma encoding. Kept together so [`crate::renderer::tonemap_to_rgb8`]
+/// and the EXR writer apply (or skip) exactly these steps consistently.
+#[derive(Clone, Copy, Debug)]
+pub struct ToneMapping {
+    pub operator: Operator,
+    /// Linear multiplier applied before the tone curve; `1.0` is neutral.
+    pub exposure: f32,
+    /// Display gamma; `2.2` matches the encoding this renderer always used
+    /// before this field existed.
+    pub gamma: f32,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self { operator: Operator::Aces, exposure: 1.0, gamma: 2.2 }
+    }
+}
+
+impl ToneMapping {
+    /// Maps linear radiance `c` to a displayable, gamma-encoded `[0, 1]`
+    /// color.
+    pub fn apply(&self, c: Vec3) -> Vec3 {
+        let exposed = c.scale(self.exposure);
+        let mapped = match self.operator {
+            Operator::Reinhard => reinhard(exposed),
+            Operator::ReinhardExtended { white } => reinhard_extended(exposed, white),
+            Operator::Aces => aces_film(exposed),
+            Operator::None => exposed,
+        };
+        mapped.map(|x| x.clamp(0.0, 1.0).powf(1.0 / self.gamma))
+    }
+}
@@ -0,0 +1,357 @@
+//! Interactive real-time viewport: an alternative to [`crate::gpu_renderer::render`]
+//! that opens a window and drives the same progressive GPU dispatch loop
+//! live instead of blocking until `scene.render.samples` finishes. Each
+//! frame runs one dispatch and blits the running, tonemapped average
+//! straight to the window surface, so the image starts noisy and cleans up
+//! the longer the camera stays still. Moving the camera (WASD + mouse look)
+//! resets the accumulation so it doesn't smear across the cut.
+
+use crate::gpu_renderer::{
+    self, create_compute_pipeline, create_persistent_resources, get_object_data, light_uniforms,
+    tonemap_pixel, CameraUniform, LightUniform, PersistentResources, RenderParams, WORKGROUP_SIZE,
+};
+use crate::scene::Scene;
+use crate::shader_builder::ShaderBuilder;
+use rand::Rng;
+use winit::event::{DeviceEvent, ElementState, Event, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::WindowBuilder;
+
+/// Scene units of camera travel per second while a WASD key is held.
+const MOVE_SPEED: f32 = 3.0;
+/// Radians per pixel of raw mouse-motion delta; the cursor is not captured,
+/// so any mouse motion turns the camera.
+const LOOK_SPEED: f32 = 0.002;
+
+struct LookState {
+    pos: crate::algebra::Vec3,
+    yaw: f32,
+    pitch: f32,
+}
+
+/// Opens a window and renders `scene` interactively until it's closed.
+pub fn run(mut scene: Scene) {
+    let tonemapping = scene.render.tonemap.resolve();
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title("Pathtracer — live viewport")
+        .with_inner_size(winit::dpi::LogicalSize::new(scene.render.width, scene.render.height))
+        .build(&event_loop)
+        .expect("failed to create window");
+
+    let instance = wgpu::Instance::default();
+    let surface = unsafe { instance.create_surface(&window) }.expect("failed to create surface");
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        compatible_surface: Some(&surface),
+        ..Default::default()
+    }))
+    .expect("failed to find GPU adapter");
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: Some("Viewport Device"),
+            features: wgpu::Features::empty(),
+            limits: adapter.limits(),
+        },
+        None,
+    ))
+    .expect("failed to create device");
+
+    let size = window.inner_size();
+    let surface_format = surface.get_capabilities(&adapter).formats[0];
+    let mut config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+        format: surface_format,
+        width: size.width.max(1),
+        height: size.height.max(1),
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        view_formats: vec![],
+    };
+    surface.configure(&device, &config);
+
+    let (spheres, planes, tris, sphere_count, plane_count, tri_count) = get_object_data(&scene);
+    assert!(!scene.lights.is_empty(), "scene needs at least one light");
+    let lights = light_uniforms(&scene.lights);
+
+    let shader_source = ShaderBuilder::new(include_str!("gpu_pathtrace.wgsl"))
+        .with_fragment("common", include_str!("shaders/common.wgsl"))
+        .with_fragment("intersect", include_str!("shaders/intersect.wgsl"))
+        .with_fragment("shading", include_str!("shaders/shading.wgsl"))
+        .with_define("VOLUMETRICS", "1")
+        .with_define("WORKGROUP_SIZE", WORKGROUP_SIZE.to_string())
+        .build();
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Viewport Pathtrace Shader"),
+        source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+    });
+    let pipeline = create_compute_pipeline(&device, &shader);
+
+    let mut look = LookState {
+        pos: scene.camera.pos,
+        yaw: 0.0,
+        pitch: 0.0,
+    };
+    {
+        let forward = (scene.camera.look_at - scene.camera.pos).normalize();
+        look.pitch = forward.1.asin();
+        look.yaw = forward.2.atan2(forward.0);
+    }
+
+    let mut resources = build_resources(
+        &device, &pipeline, &scene, &lights, &spheres, &planes, &tris,
+        sphere_count, plane_count, tri_count,
+    );
+    let mut samples_accumulated: u32 = 0;
+    let mut rng = rand::thread_rng();
+    let mut last_frame = std::time::Instant::now();
+    let mut held_keys = std::collections::HashSet::new();
+    let mut mouse_delta = (0.0f32, 0.0f32);
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Poll;
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(new_size) => {
+                    config.width = new_size.width.max(1);
+                    config.height = new_size.height.max(1);
+                    surface.configure(&device, &config);
+                }
+                WindowEvent::KeyboardInput { input, .. } => {
+                    if let Some(key) = input.virtual_keycode {
+                        match input.state {
+                            ElementState::Pressed => {
+                                held_keys.insert(key);
+                            }
+                            ElementState::Released => {
+                                held_keys.remove(&key);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                mouse_delta.0 += delta.0 as f32;
+                mouse_delta.1 += delta.1 as f32;
+            }
+            Event::MainEventsCleared => {
+                let dt = last_frame.elapsed().as_secs_f32();
+                last_frame = std::time::Instant::now();
+
+                let moved = apply_input(&mut look, &held_keys, &mut mouse_delta, dt);
+                if moved {
+                    update_camera(&mut scene, &look);
+                    resources = build_resources(
+                        &device, &pipeline, &scene, &lights, &spheres, &planes, &tris,
+                        sphere_count, plane_count, tri_count,
+                    );
+                    samples_accumulated = 0;
+                }
+
+                dispatch_one_pass(
+                    &device, &queue, &pipeline, &resources,
+                    scene.render.width, scene.render.height, &mut rng,
+                );
+                samples_accumulated += 1;
+
+                present_frame(&device, &queue, &surface, &resources, scene.render.width, scene.render.height, samples_accumulated, &tonemapping);
+            }
+            _ => {}
+        }
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_resources(
+    device: &wgpu::Device,
+    pipeline: &wgpu::ComputePipeline,
+    scene: &Scene,
+    lights: &[LightUniform],
+    spheres: &[gpu_renderer::SphereData],
+    planes: &[gpu_renderer::PlaneData],
+    tris: &[gpu_renderer::TriangleData],
+    sphere_count: u32,
+    plane_count: u32,
+    tri_count: u32,
+) -> PersistentResources {
+    let forward = (scene.camera.look_at - scene.camera.pos).normalize();
+    let right = scene.camera.up.cross(forward).normalize();
+    let up = right.cross(forward);
+    let focus_dist = crate::renderer::autofocus(
+        scene.camera.pos, right, up, forward,
+        scene.render.width as f32 / scene.render.height as f32,
+        (scene.camera.fov.to_radians() * 0.5).tan(),
+        scene.render.width, scene.render.height,
+        &scene.objects, &scene.bvh,
+    );
+    let cam = CameraUniform {
+        pos: [scene.camera.pos.0, scene.camera.pos.1, scene.camera.pos.2, 0.0],
+        forward: [forward.0, forward.1, forward.2, 0.0],
+        up: [up.0, up.1, up.2, 0.0],
+        right: [right.0, right.1, right.2, 0.0],
+        width: scene.render.width,
+        height: scene.render.height,
+        fov: scene.camera.fov,
+        sphere_count,
+        plane_count,
+        triangle_count: tri_count,
+        aperture: scene.camera.aperture,
+        focus_dist,
+        light_count: lights.len() as u32,
+        _pad0: 0,
+        _pad1: 0,
+        _pad2: 0,
+    };
+    create_persistent_resources(
+        device, pipeline, &cam, lights, spheres, planes, tris,
+        scene.render.width, scene.render.height,
+    )
+}
+
+/// Reads WASD + accumulated mouse delta since the last frame, updates
+/// `look` in place, and reports whether the camera actually moved (so the
+/// caller knows whether to reset accumulation).
+fn apply_input(
+    look: &mut LookState,
+    held_keys: &std::collections::HashSet<VirtualKeyCode>,
+    mouse_delta: &mut (f32, f32),
+    dt: f32,
+) -> bool {
+    let mut moved = mouse_delta.0 != 0.0 || mouse_delta.1 != 0.0;
+    look.yaw += mouse_delta.0 * LOOK_SPEED;
+    look.pitch = (look.pitch - mouse_delta.1 * LOOK_SPEED).clamp(-1.5, 1.5);
+    *mouse_delta = (0.0, 0.0);
+
+    let forward = crate::algebra::Vec3(
+        look.yaw.cos() * look.pitch.cos(),
+        look.pitch.sin(),
+        look.yaw.sin() * look.pitch.cos(),
+    )
+    .normalize();
+    let world_up = crate::algebra::Vec3(0.0, 1.0, 0.0);
+    let right = world_up.cross(forward).normalize();
+
+    let step = MOVE_SPEED * dt;
+    if held_keys.contains(&VirtualKeyCode::W) {
+        look.pos = look.pos + forward.scale(step);
+        moved = true;
+    }
+    if held_keys.contains(&VirtualKeyCode::S) {
+        look.pos = look.pos - forward.scale(step);
+        moved = true;
+    }
+    if held_keys.contains(&VirtualKeyCode::A) {
+        look.pos = look.pos + right.scale(step);
+        moved = true;
+    }
+    if held_keys.contains(&VirtualKeyCode::D) {
+        look.pos = look.pos - right.scale(step);
+        moved = true;
+    }
+    moved
+}
+
+fn update_camera(scene: &mut Scene, look: &LookState) {
+    let forward = crate::algebra::Vec3(
+        look.yaw.cos() * look.pitch.cos(),
+        look.pitch.sin(),
+        look.yaw.sin() * look.pitch.cos(),
+    )
+    .normalize();
+    scene.camera.pos = look.pos;
+    scene.camera.look_at = look.pos + forward;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn dispatch_one_pass(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    pipeline: &wgpu::ComputePipeline,
+    resources: &PersistentResources,
+    width: u32,
+    height: u32,
+    rng: &mut impl Rng,
+) {
+    let params = RenderParams {
+        samples_per_pixel: 1,
+        max_bounces: 12,
+        seed1: rng.gen(),
+        seed2: rng.gen(),
+    };
+    queue.write_buffer(&resources.params_buffer, 0, bytemuck::bytes_of(&params));
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Viewport Encoder"),
+    });
+    {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Viewport Compute Pass"),
+        });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &resources.bind_group, &[]);
+        cpass.dispatch_workgroups(
+            (width + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+            (height + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+            1,
+        );
+    }
+    queue.submit(Some(encoder.finish()));
+}
+
+/// Reads the accumulator back, tonemaps it, and writes it straight into the
+/// surface texture — no vertex/fragment render pipeline, just a blit.
+#[allow(clippy::too_many_arguments)]
+fn present_frame(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    surface: &wgpu::Surface,
+    resources: &PersistentResources,
+    width: u32,
+    height: u32,
+    samples_accumulated: u32,
+    tonemapping: &crate::tonemap::ToneMapping,
+) {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(
+        &resources.output_buffer, 0, &resources.staging_buffer, 0, resources.output_buffer_size,
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = resources.staging_buffer.slice(..);
+    let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |v| tx.send(v).unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    pollster::block_on(rx.receive()).unwrap().expect("map failed");
+
+    let data = buffer_slice.get_mapped_range();
+    let pixels: &[[f32; 4]] = bytemuck::cast_slice(&data);
+    let mut rgba = vec![0u8; (width * height * 4) as usize];
+    for (i, pixel) in pixels.iter().enumerate() {
+        let [r, g, b, a] = tonemap_pixel(*pixel, samples_accumulated.max(1), tonemapping);
+        rgba[i * 4] = r;
+        rgba[i * 4 + 1] = g;
+        rgba[i * 4 + 2] = b;
+        rgba[i * 4 + 3] = a;
+    }
+    drop(data);
+    resources.staging_buffer.unmap();
+
+    let surface_texture = match surface.get_current_texture() {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+    queue.write_texture(
+        surface_texture.texture.as_image_copy(),
+        &rgba,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 4),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    surface_texture.present();
+}